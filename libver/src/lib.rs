@@ -10,14 +10,145 @@
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{HashMap, VecDeque},
-    env::{self, VarError, home_dir},
+    env::{self, home_dir},
     fmt::Display,
     fs::read_to_string,
     io::{Error, ErrorKind, Result as IoResult},
     path::PathBuf,
-    process::Command,
+    process::{Child, Command, ExitStatus},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
 };
 
+/// Runs `f` on a background thread, returning its result if it completes within
+/// `timeout`, or a timeout error if `timeout` elapses first. A `None` timeout
+/// runs `f` on the current thread with no deadline.
+fn run_with_timeout<T: Send + 'static, F: FnOnce() -> IoResult<T> + Send + 'static>(
+    f: F,
+    timeout: Option<Duration>,
+) -> IoResult<T> {
+    let Some(timeout) = timeout else {
+        return f();
+    };
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(f());
+    });
+    rx.recv_timeout(timeout)
+        .unwrap_or_else(|_| Err(Error::new(ErrorKind::TimedOut, "Operation timed out")))
+}
+
+/// Spawns `cmd` and waits for it to exit, killing it and returning a
+/// [`ErrorKind::TimedOut`] error if `timeout` elapses first. A `None` timeout
+/// waits indefinitely, as [`Command::status`] would.
+pub fn wait_with_timeout(cmd: &mut Command, timeout: Option<Duration>) -> IoResult<ExitStatus> {
+    let mut child: Child = cmd.spawn()?;
+    let Some(timeout) = timeout else {
+        return child.wait();
+    };
+    let start: Instant = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if start.elapsed() >= timeout {
+            child.kill()?;
+            let _ = child.wait();
+            return Err(Error::new(
+                ErrorKind::TimedOut,
+                format!("process exceeded the configured timeout of {:?}", timeout),
+            ));
+        }
+        thread::sleep(Duration::from_millis(25));
+    }
+}
+
+/// Stable, documented exit codes for the `verune`/`verstring` binaries.
+///
+/// This replaces the ad-hoc `(i32, String, bool)` status tuples each binary's
+/// `main` used to hand-roll, so scripts can depend on a fixed, numbered set of
+/// outcomes instead of parsing messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    /// Everything succeeded.
+    Success,
+    /// The arguments passed to the binary were invalid, or no subcommand was given.
+    Usage,
+    /// The configuration could not be read, parsed, or written.
+    ConfigError,
+    /// A named runtime does not exist.
+    RuntimeNotFound,
+    /// A runtime exists, but the requested version is not installed.
+    VersionUnavailable,
+    /// A scoped program could not be started.
+    ExecutionError,
+    /// An external process exceeded a configured timeout.
+    TimedOut,
+    /// A scoped program was interrupted before it could report an exit status.
+    Interrupted,
+    /// A scoped program exited on its own with the given status code, which is
+    /// forwarded as-is.
+    Raw(i32),
+}
+
+impl ExitCode {
+    /// The numeric status that should be passed to [std::process::exit].
+    pub fn code(self) -> i32 {
+        match self {
+            ExitCode::Success => 0,
+            ExitCode::ConfigError => 1,
+            ExitCode::Usage => 2,
+            ExitCode::RuntimeNotFound => 3,
+            ExitCode::VersionUnavailable => 4,
+            ExitCode::ExecutionError => 5,
+            ExitCode::TimedOut => 124,
+            ExitCode::Interrupted => 143,
+            ExitCode::Raw(code) => code,
+        }
+    }
+
+    /// A short, human-readable description of this exit code.
+    pub fn message(self) -> &'static str {
+        match self {
+            ExitCode::Success => "success",
+            ExitCode::ConfigError => "a configuration error occurred",
+            ExitCode::Usage => "invalid arguments were provided",
+            ExitCode::RuntimeNotFound => "the requested runtime could not be found",
+            ExitCode::VersionUnavailable => "the requested version is not installed",
+            ExitCode::ExecutionError => "the scoped program could not be executed",
+            ExitCode::TimedOut => "an external process exceeded the configured timeout",
+            ExitCode::Interrupted => "the scoped program was interrupted",
+            ExitCode::Raw(_) => "the scoped program exited with a non-zero status",
+        }
+    }
+}
+
+/// Wraps a lower-level parse error with caller-facing context, while keeping
+/// the original error reachable via [std::error::Error::source]. This lets a
+/// caller that only wants a message use the `io::Error`'s [Display] as
+/// before, while a caller that wants the structured context a format's own
+/// error type carries (e.g. [ruleport::Error]'s span/kind/field path) can
+/// still recover it by walking `source()` and downcasting, instead of it
+/// being lost the moment the original error is stringified.
+#[derive(Debug)]
+struct ContextError {
+    message: String,
+    source: Box<dyn std::error::Error + Send + Sync>,
+}
+
+impl std::fmt::Display for ContextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {}", self.message, self.source)
+    }
+}
+
+impl std::error::Error for ContextError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
 /// Basic metadata representation for a [Runtime].
 ///
 /// [Runtime]s will typically be composed of this struct in order to allow
@@ -29,6 +160,16 @@ pub struct RuntimeMetadata {
     pub search_paths: Vec<String>,
 }
 
+impl RuntimeMetadata {
+    /// Serializes this metadata into the crate's own compact `ver` format,
+    /// the write-side counterpart to the `meta.ver` read path in
+    /// [Runtime::new].
+    pub fn to_ver_string(&self) -> IoResult<String> {
+        ruleport::ser::to_string_pretty(self, ruleport::ser::PrettyConfig::hierarchy())
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err.to_string()))
+    }
+}
+
 /// Basic I/O layer for a runtime.
 ///
 /// This structure both contains data about a runtime and keeps track of its
@@ -51,26 +192,51 @@ impl Runtime {
 
     /// Attempts to create a [Runtime].
     ///
+    /// Metadata is read from `meta.ver` (the crate's own, more compact
+    /// format) when present, falling back to the legacy `meta.ron` otherwise.
+    ///
     /// Note that this method can fail in the following cases:
     ///
     /// - The home directory is inaccessible
     /// - The metadata file can't be read
     /// - The metadata file can't be deserialized
     pub fn new<T: Display>(name: T) -> IoResult<Runtime> {
-        let mut buf: PathBuf = Runtime::get_runtime(&name)?;
-        buf.push("meta.ron");
-        let data: String = read_to_string(buf)?;
-        match ron::from_str::<RuntimeMetadata>(data.as_str()) {
+        let runtime_dir: PathBuf = Runtime::get_runtime(&name)?;
+        let mut ver_path: PathBuf = runtime_dir.clone();
+        ver_path.push("meta.ver");
+        let (path, data): (PathBuf, String) = if ver_path.try_exists()? {
+            let data: String = read_to_string(&ver_path)?;
+            (ver_path, data)
+        } else {
+            let mut ron_path: PathBuf = runtime_dir;
+            ron_path.push("meta.ron");
+            let data: String = read_to_string(&ron_path)?;
+            (ron_path, data)
+        };
+        let metadata: std::result::Result<
+            RuntimeMetadata,
+            Box<dyn std::error::Error + Send + Sync>,
+        > = if path.extension().is_some_and(|ext| ext == "ver") {
+            ruleport::from_str(data.as_str())
+                .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync>)
+        } else {
+            ron::from_str(data.as_str())
+                .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync>)
+        };
+        match metadata {
             Ok(metadata) => Ok(Runtime {
                 name: name.to_string(),
                 metadata,
             }),
-            Err(_) => Err(Error::new(
+            Err(source) => Err(Error::new(
                 ErrorKind::InvalidData,
-                format!(
-                    "Metadata file for runtime \"{}\" is not valid runtime metadata",
-                    name
-                ),
+                ContextError {
+                    message: format!(
+                        "Metadata file for runtime \"{}\" is not valid runtime metadata",
+                        name
+                    ),
+                    source,
+                },
             )),
         }
     }
@@ -104,28 +270,77 @@ impl Runtime {
 
     /// Checks if a version directory exists, returning it if it does.
     pub fn get_safe_version<T: Display>(&self, version: T) -> IoResult<PathBuf> {
+        self.get_safe_version_timeout(version, None)
+    }
+
+    /// Checks if a version directory exists, returning it if it does, bounding
+    /// the check to `timeout` so that a hung filesystem can't block the caller
+    /// indefinitely.
+    pub fn get_safe_version_timeout<T: Display>(
+        &self,
+        version: T,
+        timeout: Option<Duration>,
+    ) -> IoResult<PathBuf> {
         let path: PathBuf = self.get_version(version.to_string())?;
-        if path.try_exists()? {
-            Ok(path)
-        } else {
-            Err(Error::new(
-                ErrorKind::NotFound,
-                format!(
-                    "Version {} for runtime \"{}\" was not found",
-                    version, self.name
-                ),
-            ))
-        }
+        let name: String = self.name.clone();
+        let version: String = version.to_string();
+        let name_for_error: String = name.clone();
+        let version_for_error: String = version.clone();
+        run_with_timeout(
+            move || {
+                if path.try_exists()? {
+                    Ok(path)
+                } else {
+                    Err(Error::new(
+                        ErrorKind::NotFound,
+                        format!("Version {} for runtime \"{}\" was not found", version, name),
+                    ))
+                }
+            },
+            timeout,
+        )
+        .map_err(|e| {
+            if e.kind() == ErrorKind::TimedOut {
+                Error::new(
+                    ErrorKind::TimedOut,
+                    format!(
+                        "Checking runtime \"{}\" version {} exceeded the configured timeout of {:?}",
+                        name_for_error,
+                        version_for_error,
+                        timeout.unwrap_or_default()
+                    ),
+                )
+            } else {
+                e
+            }
+        })
     }
 
     /// Gets the list of version search paths as relative paths to the version
     /// directory, checking if each one exists.
     pub fn get_version_search_paths<T: Display>(&self, version: T) -> IoResult<Vec<PathBuf>> {
+        self.get_version_search_paths_timeout(version, None)
+    }
+
+    /// Equivalent to [Runtime::get_version_search_paths], but bounding the
+    /// underlying version check to `timeout`.
+    pub fn get_version_search_paths_timeout<T: Display>(
+        &self,
+        version: T,
+        timeout: Option<Duration>,
+    ) -> IoResult<Vec<PathBuf>> {
+        let version: String = version.to_string();
         let mut paths: Vec<PathBuf> = Vec::new();
-        let version_dir: PathBuf = self.get_safe_version(version)?;
+        let version_dir: PathBuf = self.get_safe_version_timeout(version.clone(), timeout)?;
+        let runtime_dir: PathBuf = Runtime::get_runtime(&self.name)?;
         for search_path in &self.metadata.search_paths {
-            let search_buf: PathBuf = search_path.into();
-            let proper_search_buf: PathBuf = version_dir.join(search_buf);
+            let expanded: String = template::expand(
+                search_path,
+                &version,
+                &self.name,
+                runtime_dir.to_string_lossy().as_ref(),
+            )?;
+            let proper_search_buf: PathBuf = version_dir.join(PathBuf::from(expanded));
             if proper_search_buf.try_exists()? {
                 paths.push(proper_search_buf);
             } else {
@@ -142,25 +357,193 @@ impl Runtime {
     }
 }
 
+pub mod template {
+    use crate::*;
+    use std::io::{Error, ErrorKind, Result as IoResult};
+
+    /// Expands `{version}`, `{runtime}`, `{runtime_dir}`, and `{env:VARNAME}`
+    /// placeholders in `input` against `version`, `runtime`, and `runtime_dir`,
+    /// reading `{env:VARNAME}` from the current environment (defaulting to an
+    /// empty string if unset). Literal braces are written as `{{`/`}}`.
+    ///
+    /// Parsing is a single left-to-right pass: an unterminated `{` or an
+    /// unrecognized `{key}` form produces a descriptive error.
+    pub fn expand(
+        input: &str,
+        version: &str,
+        runtime: &str,
+        runtime_dir: &str,
+    ) -> IoResult<String> {
+        let mut output: String = String::with_capacity(input.len());
+        let mut chars = input.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '{' if chars.peek().is_some_and(|(_, c)| *c == '{') => {
+                    chars.next();
+                    output.push('{');
+                }
+                '}' if chars.peek().is_some_and(|(_, c)| *c == '}') => {
+                    chars.next();
+                    output.push('}');
+                }
+                '{' => {
+                    let mut end: Option<usize> = None;
+                    for (j, c) in chars.by_ref() {
+                        if c == '}' {
+                            end = Some(j);
+                            break;
+                        }
+                    }
+                    let Some(end) = end else {
+                        return Err(Error::new(
+                            ErrorKind::InvalidInput,
+                            format!("Unterminated placeholder starting at position {}", i),
+                        ));
+                    };
+                    let key: &str = &input[i + 1..end];
+                    match key {
+                        "version" => output.push_str(version),
+                        "runtime" => output.push_str(runtime),
+                        "runtime_dir" => output.push_str(runtime_dir),
+                        _ if key.starts_with("env:") => {
+                            output.push_str(env::var(&key[4..]).unwrap_or_default().as_str())
+                        }
+                        _ => {
+                            return Err(Error::new(
+                                ErrorKind::InvalidInput,
+                                format!("Unknown placeholder \"{{{}}}\"", key),
+                            ));
+                        }
+                    }
+                }
+                '}' => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("Unescaped \"}}\" at position {}", i),
+                    ));
+                }
+                _ => output.push(c),
+            }
+        }
+        Ok(output)
+    }
+}
+
 pub mod conf {
     use crate::*;
     use std::{
-        io::{Error, ErrorKind, Result as IoResult},
-        path::Path,
+        io::{Error, ErrorKind, Read, Result as IoResult, Write, stdin, stdout},
+        path::{Path, PathBuf},
     };
 
-    /// Reads a configuration file, and then parses it.
+    /// Returns `true` if `path` is the conventional `-` sentinel for stdin/stdout,
+    /// allowing a configuration to be piped in or out instead of read from disk.
+    pub fn is_stdio<T: AsRef<Path>>(path: T) -> bool {
+        path.as_ref() == Path::new("-")
+    }
+
+    /// Reads a configuration file, and then parses it. `-` reads from stdin
+    /// instead of from disk. Like [Runtime::new], a `.ver` extension is
+    /// parsed with `ruleport`; anything else is parsed as RON.
     pub fn parse<T: AsRef<Path>>(path: T) -> IoResult<HashMap<String, String>> {
-        let data: String = read_to_string(path.as_ref())?;
-        match ron::from_str::<HashMap<String, String>>(data.as_str()) {
+        let data: String = if is_stdio(&path) {
+            let mut buf: String = String::new();
+            stdin().read_to_string(&mut buf)?;
+            buf
+        } else {
+            read_to_string(path.as_ref())?
+        };
+        let is_ver: bool = path.as_ref().extension().is_some_and(|ext| ext == "ver");
+        let parsed: std::result::Result<
+            HashMap<String, String>,
+            Box<dyn std::error::Error + Send + Sync>,
+        > = if is_ver {
+            ruleport::from_str(data.as_str())
+                .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync>)
+        } else {
+            ron::from_str(data.as_str())
+                .map_err(|err| Box::new(err) as Box<dyn std::error::Error + Send + Sync>)
+        };
+        match parsed {
             Ok(map) => Ok(map),
-            Err(_) => Err(Error::new(
+            Err(source) => Err(Error::new(
                 ErrorKind::InvalidData,
-                "Configuration file is invalid",
+                ContextError {
+                    message: "Configuration file is invalid".into(),
+                    source,
+                },
             )),
         }
     }
 
+    /// Writes `data` to a configuration file, or to stdout if `path` is `-`.
+    pub fn write<T: AsRef<Path>>(path: T, data: String) -> IoResult<()> {
+        if is_stdio(&path) {
+            stdout().write_all(data.as_bytes())
+        } else {
+            std::fs::write(path.as_ref(), data)
+        }
+    }
+
+    /// Walks up from `start` towards the filesystem root, returning the nearest
+    /// `.ver.ron` found along the way.
+    pub fn find_local<T: AsRef<Path>>(start: T) -> Option<PathBuf> {
+        let mut dir: &Path = start.as_ref();
+        loop {
+            let candidate: PathBuf = dir.join(".ver.ron");
+            if candidate.try_exists().unwrap_or(false) {
+                return Some(candidate);
+            }
+            dir = dir.parent()?;
+        }
+    }
+
+    /// Returns the path to the global configuration file, under
+    /// `$XDG_CONFIG_HOME/verune/global.ron` (or `$HOME/.config/...` if unset).
+    pub fn global_path() -> IoResult<PathBuf> {
+        let mut buf: PathBuf = if let Ok(xdg) = env::var("XDG_CONFIG_HOME") {
+            xdg.into()
+        } else if let Some(mut home) = home_dir() {
+            home.push(".config");
+            home
+        } else {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                "Could not determine the global configuration directory",
+            ));
+        };
+        buf.push("verune");
+        buf.push("global.ron");
+        Ok(buf)
+    }
+
+    /// Discovers and merges the effective configuration for the current directory:
+    /// the global configuration is read first, and then overridden key-by-key by
+    /// the nearest local `.ver.ron`, if one is found.
+    pub fn discover<T: AsRef<Path>>(start: T) -> IoResult<HashMap<String, String>> {
+        let mut merged: HashMap<String, String> = global_path().and_then(parse).unwrap_or_default();
+        if let Some(local) = find_local(start) {
+            for (runtime, version) in parse(local)? {
+                merged.insert(runtime, version);
+            }
+        }
+        Ok(merged)
+    }
+
+    /// Returns the path to the alias table sibling to `config`, conventionally
+    /// `.ver.alias.ron` next to the configuration file it expands commands for.
+    pub fn alias_path<T: AsRef<Path>>(config: T) -> PathBuf {
+        let mut buf: PathBuf = config.as_ref().to_path_buf();
+        buf.set_file_name(".ver.alias.ron");
+        buf
+    }
+
+    /// Reads the alias table sibling to `config`, if one exists. Each value is
+    /// the whitespace-separated command an alias key expands into.
+    pub fn load_aliases<T: AsRef<Path>>(config: T) -> HashMap<String, String> {
+        parse(alias_path(config)).unwrap_or_default()
+    }
+
     /// Returns a transformed variant of a configuration file using
     /// [Runtime::unsafe_new].
     pub fn unsafe_collect(data: HashMap<String, String>) -> HashMap<Runtime, String> {
@@ -181,6 +564,112 @@ pub mod conf {
         }
         Ok(parsed)
     }
+
+    /// One layer of configuration for a [Builder], in the order it should be
+    /// merged.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Source {
+        /// A configuration file on disk, parsed with [parse].
+        File(PathBuf),
+        /// `VER_<RUNTIME>=<version>` environment variables, with `<RUNTIME>`
+        /// lower-cased back into the runtime name. `VER_SCOPE`, `VER_OVERRIDE`,
+        /// `VER_BASE_PATH`, `VER_CONFIG`, and `VER_LOG` are used for other
+        /// purposes by `libver` and its binaries and are never treated as
+        /// overrides.
+        Env,
+    }
+
+    /// Which [Source] supplied the version that ultimately won for a runtime.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum Provenance {
+        /// The file at this path set the value.
+        File(PathBuf),
+        /// A `VER_<RUNTIME>` environment variable set the value.
+        Env,
+    }
+
+    const RESERVED_ENV_KEYS: [&str; 5] = [
+        "VER_SCOPE",
+        "VER_OVERRIDE",
+        "VER_BASE_PATH",
+        "VER_CONFIG",
+        "VER_LOG",
+    ];
+
+    /// Reads `VER_<RUNTIME>=<version>` environment variables into a
+    /// `runtime -> version` layer, skipping the names `libver` reserves for
+    /// other purposes.
+    fn env_overrides() -> HashMap<String, String> {
+        let mut overrides: HashMap<String, String> = HashMap::new();
+        for (key, value) in env::vars() {
+            if RESERVED_ENV_KEYS.contains(&key.as_str()) {
+                continue;
+            }
+            if let Some(name) = key.strip_prefix("VER_") {
+                overrides.insert(name.to_lowercase(), value);
+            }
+        }
+        overrides
+    }
+
+    /// Merges an ordered list of [Source]s into a single `runtime -> version`
+    /// mapping, the way a layered configuration system (system default, user
+    /// file, environment overrides) is expected to behave: later sources
+    /// override earlier ones key-by-key, so a key present only in a
+    /// lower-priority layer still survives. Put [Source::Env] last to give
+    /// environment overrides the final say.
+    #[derive(Default)]
+    pub struct Builder {
+        sources: Vec<Source>,
+    }
+
+    impl Builder {
+        /// Creates an empty [Builder] with no sources yet.
+        pub fn new() -> Builder {
+            Builder::default()
+        }
+
+        /// Appends a [Source] to the end of the merge order.
+        pub fn source(mut self, source: Source) -> Builder {
+            self.sources.push(source);
+            self
+        }
+
+        /// Reads and merges every configured source, recording which [Source]
+        /// supplied each key's final value.
+        pub fn merge(&self) -> IoResult<(HashMap<String, String>, HashMap<String, Provenance>)> {
+            let mut merged: HashMap<String, String> = HashMap::new();
+            let mut provenance: HashMap<String, Provenance> = HashMap::new();
+            for source in &self.sources {
+                let (layer, mark): (HashMap<String, String>, Provenance) = match source {
+                    Source::File(path) => (parse(path)?, Provenance::File(path.clone())),
+                    Source::Env => (env_overrides(), Provenance::Env),
+                };
+                for (runtime, version) in layer {
+                    provenance.insert(runtime.clone(), mark.clone());
+                    merged.insert(runtime, version);
+                }
+            }
+            Ok((merged, provenance))
+        }
+
+        /// Merges the configured sources, then validates each runtime with the
+        /// strict [collect] (backed by [Runtime::new]).
+        pub fn collect(&self) -> IoResult<(HashMap<Runtime, String>, HashMap<String, Provenance>)> {
+            let (merged, provenance) = self.merge()?;
+            Ok((collect(merged)?, provenance))
+        }
+
+        /// Merges the configured sources, then collects each runtime with the
+        /// lenient [unsafe_collect] (backed by [Runtime::unsafe_new]), which
+        /// never fails even if a runtime's metadata is missing or invalid.
+        pub fn unsafe_collect(
+            &self,
+        ) -> IoResult<(HashMap<Runtime, String>, HashMap<String, Provenance>)> {
+            let (merged, provenance) = self.merge()?;
+            Ok((unsafe_collect(merged), provenance))
+        }
+    }
 }
 
 /// Executes a program with an environment suited to various runtime versions.
@@ -208,7 +697,17 @@ pub fn exec<T: Into<VecDeque<String>>>(
         paths.push(runtime.get_safe_version(version)?);
         paths.extend(runtime.get_version_search_paths(version)?);
     }
-    let path: Result<String, VarError> = env::var("PATH");
+    // Anchors the prepended runtime directories to the PATH the shell had
+    // before *any* scoping took place, rather than whatever PATH this
+    // process inherited. Without this, re-resolving a scope (e.g. a shell
+    // hook that calls back in on every prompt) would prepend onto an
+    // already-prepended PATH from the previous resolution, growing it
+    // without bound as the user moves between differently-configured
+    // directories. $VER_BASE_PATH is propagated to the child below so the
+    // same base is reused if it scopes again itself.
+    let base_path: String = env::var("VER_BASE_PATH")
+        .or_else(|_| env::var("PATH"))
+        .unwrap_or_default();
     cmd.args(deque)
         .env("PATH", {
             let mut iter = paths.iter();
@@ -226,9 +725,9 @@ pub fn exec<T: Into<VecDeque<String>>>(
                     prefix.push_str(data);
                 }
             }
-            if let Ok(good_path) = path {
+            if !base_path.is_empty() {
                 prefix.push(delim);
-                prefix.push_str(good_path.as_str());
+                prefix.push_str(&base_path);
             }
             prefix
         })
@@ -242,7 +741,507 @@ pub fn exec<T: Into<VecDeque<String>>>(
             }
             .to_string()
         })
+        .env("VER_BASE_PATH", &base_path)
         // $VER_OVERRIDE only remains for legacy purposes. It'll be removed in a later commit.
         .env("VER_OVERRIDE", "1");
     Ok(cmd)
 }
+
+/// Converts a resolved runtime/version selection to and from portable CLI
+/// arguments (`--runtime name=version`), so it can be logged, printed by a
+/// `--dry-run` mode, or replayed to re-invoke [exec] elsewhere (under `sudo`,
+/// in a container, and so on) without re-resolving the configuration.
+pub mod argv {
+    use crate::*;
+    use serde::ser::{self, Impossible, SerializeMap};
+    use std::fmt::{self, Display};
+
+    /// The flag each runtime/version pair is introduced with.
+    const FLAG: &str = "--runtime";
+
+    /// The failure mode of [ArgvSerializer] and [StringExtractor]; never
+    /// actually produced, since [Selection] only ever serializes strings
+    /// through a map.
+    #[derive(Debug)]
+    struct SerializeError(String);
+
+    impl Display for SerializeError {
+        fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str(&self.0)
+        }
+    }
+
+    impl std::error::Error for SerializeError {}
+
+    impl ser::Error for SerializeError {
+        fn custom<T: Display>(msg: T) -> Self {
+            SerializeError(msg.to_string())
+        }
+    }
+
+    fn serialize_error<T: Display>(msg: T) -> SerializeError {
+        SerializeError(msg.to_string())
+    }
+
+    /// Pulls the `String` out of a `Serialize` value known to serialize as a
+    /// string, which is all a [Runtime]'s name and its version are.
+    struct StringExtractor;
+
+    macro_rules! unsupported {
+        ($message:expr; $($fn_name:ident($($arg:ident: $arg_ty:ty),*) -> $ret:ty;)*) => {
+            $(
+                fn $fn_name(self, $($arg: $arg_ty),*) -> Result<$ret, SerializeError> {
+                    $(let _ = $arg;)*
+                    Err(serialize_error($message))
+                }
+            )*
+        };
+    }
+
+    impl ser::Serializer for StringExtractor {
+        type Ok = String;
+        type Error = SerializeError;
+        type SerializeSeq = Impossible<String, SerializeError>;
+        type SerializeTuple = Impossible<String, SerializeError>;
+        type SerializeTupleStruct = Impossible<String, SerializeError>;
+        type SerializeTupleVariant = Impossible<String, SerializeError>;
+        type SerializeMap = Impossible<String, SerializeError>;
+        type SerializeStruct = Impossible<String, SerializeError>;
+        type SerializeStructVariant = Impossible<String, SerializeError>;
+
+        fn serialize_str(self, v: &str) -> Result<String, SerializeError> {
+            Ok(v.to_string())
+        }
+
+        unsupported! {
+            "expected a string";
+            serialize_bool(v: bool) -> String;
+            serialize_i8(v: i8) -> String;
+            serialize_i16(v: i16) -> String;
+            serialize_i32(v: i32) -> String;
+            serialize_i64(v: i64) -> String;
+            serialize_u8(v: u8) -> String;
+            serialize_u16(v: u16) -> String;
+            serialize_u32(v: u32) -> String;
+            serialize_u64(v: u64) -> String;
+            serialize_f32(v: f32) -> String;
+            serialize_f64(v: f64) -> String;
+            serialize_char(v: char) -> String;
+            serialize_bytes(v: &[u8]) -> String;
+            serialize_none() -> String;
+            serialize_unit() -> String;
+            serialize_unit_struct(name: &'static str) -> String;
+        }
+
+        fn serialize_some<T: ?Sized + ser::Serialize>(
+            self,
+            _value: &T,
+        ) -> Result<String, SerializeError> {
+            Err(serialize_error("expected a string"))
+        }
+
+        fn serialize_unit_variant(
+            self,
+            _name: &'static str,
+            _variant_index: u32,
+            _variant: &'static str,
+        ) -> Result<String, SerializeError> {
+            Err(serialize_error("expected a string"))
+        }
+
+        fn serialize_newtype_struct<T: ?Sized + ser::Serialize>(
+            self,
+            _name: &'static str,
+            _value: &T,
+        ) -> Result<String, SerializeError> {
+            Err(serialize_error("expected a string"))
+        }
+
+        fn serialize_newtype_variant<T: ?Sized + ser::Serialize>(
+            self,
+            _name: &'static str,
+            _variant_index: u32,
+            _variant: &'static str,
+            _value: &T,
+        ) -> Result<String, SerializeError> {
+            Err(serialize_error("expected a string"))
+        }
+
+        fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerializeError> {
+            Err(serialize_error("expected a string"))
+        }
+
+        fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerializeError> {
+            Err(serialize_error("expected a string"))
+        }
+
+        fn serialize_tuple_struct(
+            self,
+            _name: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeTupleStruct, SerializeError> {
+            Err(serialize_error("expected a string"))
+        }
+
+        fn serialize_tuple_variant(
+            self,
+            _name: &'static str,
+            _variant_index: u32,
+            _variant: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeTupleVariant, SerializeError> {
+            Err(serialize_error("expected a string"))
+        }
+
+        fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerializeError> {
+            Err(serialize_error("expected a string"))
+        }
+
+        fn serialize_struct(
+            self,
+            _name: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeStruct, SerializeError> {
+            Err(serialize_error("expected a string"))
+        }
+
+        fn serialize_struct_variant(
+            self,
+            _name: &'static str,
+            _variant_index: u32,
+            _variant: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeStructVariant, SerializeError> {
+            Err(serialize_error("expected a string"))
+        }
+    }
+
+    /// Walks a [Selection] and pushes `--runtime name=version` tokens onto a
+    /// growing argument list — the same technique as a text-emitting
+    /// serializer, except its `Ok` output accumulates argv tokens instead of
+    /// writing to a sink.
+    struct ArgvSerializer;
+
+    impl ser::Serializer for ArgvSerializer {
+        type Ok = Vec<String>;
+        type Error = SerializeError;
+        type SerializeSeq = Impossible<Vec<String>, SerializeError>;
+        type SerializeTuple = Impossible<Vec<String>, SerializeError>;
+        type SerializeTupleStruct = Impossible<Vec<String>, SerializeError>;
+        type SerializeTupleVariant = Impossible<Vec<String>, SerializeError>;
+        type SerializeMap = ArgvMap;
+        type SerializeStruct = Impossible<Vec<String>, SerializeError>;
+        type SerializeStructVariant = Impossible<Vec<String>, SerializeError>;
+
+        fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, SerializeError> {
+            Ok(ArgvMap {
+                args: Vec::with_capacity(len.unwrap_or(0) * 2),
+                key: None,
+            })
+        }
+
+        unsupported! {
+            "expected a map";
+            serialize_bool(v: bool) -> Vec<String>;
+            serialize_i8(v: i8) -> Vec<String>;
+            serialize_i16(v: i16) -> Vec<String>;
+            serialize_i32(v: i32) -> Vec<String>;
+            serialize_i64(v: i64) -> Vec<String>;
+            serialize_u8(v: u8) -> Vec<String>;
+            serialize_u16(v: u16) -> Vec<String>;
+            serialize_u32(v: u32) -> Vec<String>;
+            serialize_u64(v: u64) -> Vec<String>;
+            serialize_f32(v: f32) -> Vec<String>;
+            serialize_f64(v: f64) -> Vec<String>;
+            serialize_char(v: char) -> Vec<String>;
+            serialize_str(v: &str) -> Vec<String>;
+            serialize_bytes(v: &[u8]) -> Vec<String>;
+            serialize_none() -> Vec<String>;
+            serialize_unit() -> Vec<String>;
+            serialize_unit_struct(name: &'static str) -> Vec<String>;
+        }
+
+        fn serialize_some<T: ?Sized + ser::Serialize>(
+            self,
+            _value: &T,
+        ) -> Result<Vec<String>, SerializeError> {
+            Err(serialize_error("expected a map"))
+        }
+
+        fn serialize_unit_variant(
+            self,
+            _name: &'static str,
+            _variant_index: u32,
+            _variant: &'static str,
+        ) -> Result<Vec<String>, SerializeError> {
+            Err(serialize_error("expected a map"))
+        }
+
+        fn serialize_newtype_struct<T: ?Sized + ser::Serialize>(
+            self,
+            _name: &'static str,
+            _value: &T,
+        ) -> Result<Vec<String>, SerializeError> {
+            Err(serialize_error("expected a map"))
+        }
+
+        fn serialize_newtype_variant<T: ?Sized + ser::Serialize>(
+            self,
+            _name: &'static str,
+            _variant_index: u32,
+            _variant: &'static str,
+            _value: &T,
+        ) -> Result<Vec<String>, SerializeError> {
+            Err(serialize_error("expected a map"))
+        }
+
+        fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerializeError> {
+            Err(serialize_error("expected a map"))
+        }
+
+        fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerializeError> {
+            Err(serialize_error("expected a map"))
+        }
+
+        fn serialize_tuple_struct(
+            self,
+            _name: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeTupleStruct, SerializeError> {
+            Err(serialize_error("expected a map"))
+        }
+
+        fn serialize_tuple_variant(
+            self,
+            _name: &'static str,
+            _variant_index: u32,
+            _variant: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeTupleVariant, SerializeError> {
+            Err(serialize_error("expected a map"))
+        }
+
+        fn serialize_struct(
+            self,
+            _name: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeStruct, SerializeError> {
+            Err(serialize_error("expected a map"))
+        }
+
+        fn serialize_struct_variant(
+            self,
+            _name: &'static str,
+            _variant_index: u32,
+            _variant: &'static str,
+            _len: usize,
+        ) -> Result<Self::SerializeStructVariant, SerializeError> {
+            Err(serialize_error("expected a map"))
+        }
+    }
+
+    struct ArgvMap {
+        args: Vec<String>,
+        key: Option<String>,
+    }
+
+    impl SerializeMap for ArgvMap {
+        type Ok = Vec<String>;
+        type Error = SerializeError;
+
+        fn serialize_key<T: ?Sized + ser::Serialize>(
+            &mut self,
+            key: &T,
+        ) -> Result<(), SerializeError> {
+            self.key = Some(key.serialize(StringExtractor)?);
+            Ok(())
+        }
+
+        fn serialize_value<T: ?Sized + ser::Serialize>(
+            &mut self,
+            value: &T,
+        ) -> Result<(), SerializeError> {
+            let name = self
+                .key
+                .take()
+                .ok_or_else(|| serialize_error("value serialized before key"))?;
+            let version = value.serialize(StringExtractor)?;
+            self.args.push(FLAG.to_string());
+            self.args.push(format!("{}={}", name, version));
+            Ok(())
+        }
+
+        fn end(self) -> Result<Vec<String>, SerializeError> {
+            Ok(self.args)
+        }
+    }
+
+    /// A runtime/version selection, serialized as `name -> version` pairs so
+    /// it can be driven through [ArgvSerializer] without [Runtime] itself
+    /// needing to implement [Serialize].
+    struct Selection<'a>(&'a HashMap<Runtime, String>);
+
+    impl<'a> ser::Serialize for Selection<'a> {
+        fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut map = serializer.serialize_map(Some(self.0.len()))?;
+            for (runtime, version) in self.0 {
+                map.serialize_entry(&runtime.name, version)?;
+            }
+            map.end()
+        }
+    }
+
+    /// Serializes a resolved runtime/version selection into `--runtime
+    /// name=version` argv tokens, suitable for logging, a `--dry-run` mode,
+    /// or re-invoking the same selection elsewhere.
+    pub fn to_args(config: &HashMap<Runtime, String>) -> Vec<String> {
+        Selection(config)
+            .serialize(ArgvSerializer)
+            .expect("a runtime/version selection always serializes to strings")
+    }
+
+    /// Reconstructs a `runtime -> version` configuration from `--runtime
+    /// name=version` tokens produced by [to_args], so a captured selection
+    /// can be replayed through [conf::collect]/[conf::unsafe_collect].
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> IoResult<HashMap<String, String>> {
+        let mut config: HashMap<String, String> = HashMap::new();
+        let mut iter = args.into_iter();
+        while let Some(token) = iter.next() {
+            if token != FLAG {
+                continue;
+            }
+            let pair = iter.next().ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("\"{}\" flag is missing its name=version value", FLAG),
+                )
+            })?;
+            let Some((name, version)) = pair.split_once('=') else {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("\"{}\" is not in name=version form", pair),
+                ));
+            };
+            config.insert(name.to_string(), version.to_string());
+        }
+        Ok(config)
+    }
+}
+
+pub mod cache {
+    use crate::*;
+    use std::{
+        collections::hash_map::DefaultHasher,
+        fs::write,
+        hash::{Hash, Hasher},
+        io::Result as IoResult,
+        path::{Path, PathBuf},
+        time::SystemTime,
+    };
+
+    /// A single `check` fingerprint for one resolved `(runtime, version)` pair:
+    /// the mtime/size of its version directory and of each of its search
+    /// paths, plus a hash of the merged configuration that resolved it.
+    #[derive(PartialEq, Eq, Hash, Deserialize, Serialize)]
+    pub struct Fingerprint {
+        mtime_secs: u64,
+        size: u64,
+        search_paths_hash: u64,
+        config_hash: u64,
+    }
+
+    impl Fingerprint {
+        /// Computes the current fingerprint for `runtime`'s `version` directory
+        /// and all of its search paths, combined with `config_hash` from
+        /// [hash_config]. Folding the search paths in means a search path that
+        /// was deleted or broken without touching the version directory's own
+        /// mtime/size still invalidates the cache.
+        pub fn compute<T: Display>(
+            runtime: &Runtime,
+            version: T,
+            config_hash: u64,
+        ) -> IoResult<Fingerprint> {
+            let version: String = version.to_string();
+            let meta = runtime.get_version(&version)?.metadata()?;
+            let mut search_paths: Vec<(u64, u64)> = Vec::new();
+            for path in runtime.get_version_search_paths(&version)? {
+                let meta = path.metadata()?;
+                search_paths.push((mtime_secs(&meta), meta.len()));
+            }
+            let mut hasher = DefaultHasher::new();
+            search_paths.hash(&mut hasher);
+            Ok(Fingerprint {
+                mtime_secs: mtime_secs(&meta),
+                size: meta.len(),
+                search_paths_hash: hasher.finish(),
+                config_hash,
+            })
+        }
+    }
+
+    /// Extracts a directory entry's modification time as whole seconds since
+    /// the Unix epoch, defaulting to `0` if it's unavailable on this platform.
+    fn mtime_secs(meta: &std::fs::Metadata) -> u64 {
+        meta.modified()
+            .ok()
+            .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default()
+    }
+
+    /// Hashes `data` (the merged configuration, including overlays, `--replace`,
+    /// and `--set`) into a stable fingerprint component, independent of key order.
+    pub fn hash_config(data: &HashMap<String, String>) -> u64 {
+        let mut entries: Vec<(&String, &String)> = data.iter().collect();
+        entries.sort();
+        let mut hasher = DefaultHasher::new();
+        entries.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// The on-disk cache of [Fingerprint]s produced by `check`, keyed by
+    /// `"runtime=version"`.
+    #[derive(Default, Deserialize, Serialize)]
+    pub struct Cache(HashMap<String, Fingerprint>);
+
+    impl Cache {
+        /// Returns the path to the cache file sibling to `config`, conventionally
+        /// `.ver.cache.ron` next to the configuration file it was checked against.
+        pub fn path<T: AsRef<Path>>(config: T) -> PathBuf {
+            let mut buf: PathBuf = config.as_ref().to_path_buf();
+            buf.set_file_name(".ver.cache.ron");
+            buf
+        }
+
+        /// Reads the cache sibling to `config`, falling back to an empty cache if
+        /// it doesn't exist or can't be parsed.
+        pub fn load<T: AsRef<Path>>(config: T) -> Cache {
+            read_to_string(Cache::path(config))
+                .ok()
+                .and_then(|data| ron::from_str(data.as_str()).ok())
+                .unwrap_or_default()
+        }
+
+        /// Writes the cache to its sibling path next to `config`.
+        pub fn save<T: AsRef<Path>>(&self, config: T) -> IoResult<()> {
+            let data: String = ron::to_string(self)
+                .map_err(|e| Error::new(ErrorKind::InvalidData, e.to_string()))?;
+            write(Cache::path(config), data)
+        }
+
+        /// Returns `true` if `actual` matches the cached fingerprint for
+        /// `runtime`/`version`, meaning `check` can be skipped for this pair.
+        pub fn is_fresh(&self, runtime: &str, version: &str, actual: &Fingerprint) -> bool {
+            self.0.get(&cache_key(runtime, version)) == Some(actual)
+        }
+
+        /// Records `actual` as the fresh fingerprint for `runtime`/`version`.
+        pub fn insert(&mut self, runtime: &str, version: &str, actual: Fingerprint) {
+            self.0.insert(cache_key(runtime, version), actual);
+        }
+    }
+
+    fn cache_key(runtime: &str, version: &str) -> String {
+        format!("{}={}", runtime, version)
+    }
+}
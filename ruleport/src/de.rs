@@ -0,0 +1,887 @@
+use crate::Iterable;
+use crate::error::{Error, Result, Span};
+use serde::de::{
+    self, Deserialize, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+
+#[derive(PartialEq)]
+enum Mode {
+    Normal,
+    Key,
+}
+
+pub struct Deserializer<'de> {
+    input: &'de str,
+    pos: usize,
+    mode: Mode,
+    recovering: bool,
+    errors: Vec<Error>,
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn from_str(input: &'de str) -> Deserializer<'de> {
+        Deserializer {
+            input,
+            pos: 0,
+            mode: Mode::Normal,
+            recovering: false,
+            errors: Vec::new(),
+        }
+    }
+
+    fn line_col(&self) -> (u32, u32) {
+        let mut line: u32 = 1;
+        let mut column: u32 = 1;
+        for c in self.input[..self.pos].chars() {
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        (line, column)
+    }
+
+    /// A byte [Span] covering the single offending character at the current
+    /// position, or a zero-width span at end of input.
+    fn current_span(&self) -> Span {
+        let start = self.pos;
+        let end = self
+            .peek_char()
+            .map(|c| start + c.len_utf8())
+            .unwrap_or(start);
+        Span { start, end }
+    }
+
+    /// Recovery-mode only: records `err`, rewinds to where the failed token
+    /// started, and skips forward to the matching `enclosing_close` of the
+    /// sequence/map currently being walked, so the caller can end it
+    /// cleanly there instead of reporting a spurious "kept open" error.
+    /// Any entries between the failure and the close are necessarily
+    /// dropped along with it — there is no sync point *within* this
+    /// sequence/map that is safe to resume from without re-parsing the bad
+    /// entry, so the next safe point is wherever this one ends.
+    fn recover(&mut self, start: usize, err: Error, enclosing_close: char) {
+        self.errors.push(err);
+        self.pos = start;
+        self.skip_to_sync(enclosing_close);
+    }
+
+    /// Advances past nested brackets and quoted strings, stopping at the
+    /// first depth-0 occurrence of `enclosing_close`, or end of input.
+    fn skip_to_sync(&mut self, enclosing_close: char) {
+        let mut depth: i32 = 0;
+        loop {
+            match self.peek_char() {
+                None => return,
+                Some(c) if depth == 0 && c == enclosing_close => {
+                    return;
+                }
+                Some('"') => {
+                    self.next_char();
+                    while let Some(inner) = self.next_char() {
+                        if inner == '"' {
+                            break;
+                        }
+                    }
+                }
+                Some('[') | Some('(') | Some('{') => {
+                    depth += 1;
+                    self.next_char();
+                }
+                Some(']') | Some(')') | Some('}') => {
+                    depth -= 1;
+                    self.next_char();
+                }
+                Some(_) => {
+                    self.next_char();
+                }
+            }
+        }
+    }
+
+    fn err_unrecognized(&self) -> Error {
+        let (line, column) = self.line_col();
+        Error::unrecognized_syntax(line, column, self.current_span())
+    }
+
+    fn err_invalid_key(&self) -> Error {
+        let (line, column) = self.line_col();
+        Error::invalid_key(line, column, self.current_span())
+    }
+
+    fn err_invalid_value(&self) -> Error {
+        let (line, column) = self.line_col();
+        Error::invalid_value(line, column, self.current_span())
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.peek_char()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    /// Separators between elements are configurable on the serializer side
+    /// (`:`, ` `, or `\n` plus indentation), so any run of whitespace or
+    /// colons is accepted here and simply discarded.
+    fn skip_separators(&mut self) {
+        while let Some(c) = self.peek_char() {
+            if c.is_whitespace() || c == ':' {
+                self.pos += c.len_utf8();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn expect_char(&mut self, expected: char) -> Result<()> {
+        self.skip_separators();
+        if self.peek_char() == Some(expected) {
+            self.next_char();
+            Ok(())
+        } else {
+            Err(self.err_unrecognized())
+        }
+    }
+
+    fn expect_str(&mut self, expected: &str) -> Result<()> {
+        self.skip_separators();
+        if self.input[self.pos..].starts_with(expected) {
+            self.pos += expected.len();
+            Ok(())
+        } else {
+            Err(self.err_unrecognized())
+        }
+    }
+
+    fn parse_bool(&mut self) -> Result<bool> {
+        if self.input[self.pos..].starts_with("true") {
+            self.pos += 4;
+            Ok(true)
+        } else if self.input[self.pos..].starts_with("false") {
+            self.pos += 5;
+            Ok(false)
+        } else {
+            Err(self.err_invalid_value())
+        }
+    }
+
+    fn parse_number_token(&mut self) -> Result<&'de str> {
+        let start = self.pos;
+        let rest = &self.input[self.pos..];
+        if rest.starts_with("-inf") {
+            self.pos += 4;
+        } else if rest.starts_with("inf") {
+            self.pos += 3;
+        } else if rest.starts_with("NaN") {
+            self.pos += 3;
+        } else {
+            if self.peek_char() == Some('-') {
+                self.next_char();
+            }
+            while let Some(c) = self.peek_char() {
+                if c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || c == '+' {
+                    self.next_char();
+                } else {
+                    break;
+                }
+            }
+        }
+        if self.pos == start {
+            return Err(self.err_invalid_value());
+        }
+        Ok(&self.input[start..self.pos])
+    }
+
+    fn peek_number_is_float(&self) -> bool {
+        let rest = self.input[self.pos..]
+            .strip_prefix('-')
+            .unwrap_or(&self.input[self.pos..]);
+        if rest.starts_with("inf") || rest.starts_with("NaN") {
+            return true;
+        }
+        let end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || c == '+'))
+            .unwrap_or(rest.len());
+        rest[..end].contains('.') || rest[..end].contains('e') || rest[..end].contains('E')
+    }
+
+    fn parse_char(&mut self) -> Result<char> {
+        if self.next_char() != Some('\'') {
+            return Err(self.err_invalid_value());
+        }
+        let c = self.next_char().ok_or_else(|| self.err_invalid_value())?;
+        if self.next_char() != Some('\'') {
+            return Err(self.err_invalid_value());
+        }
+        Ok(c)
+    }
+
+    fn parse_string(&mut self) -> Result<&'de str> {
+        if self.next_char() != Some('"') {
+            return Err(self.err_invalid_value());
+        }
+        let start = self.pos;
+        while let Some(c) = self.peek_char() {
+            if c == '"' {
+                let s = &self.input[start..self.pos];
+                self.next_char();
+                return Ok(s);
+            }
+            self.next_char();
+        }
+        Err(self.err_invalid_value())
+    }
+
+    /// Keys are emitted unquoted in `SerializerMode::Key`, so a bare key runs
+    /// up to the `->` that separates it from its value.
+    fn parse_bare_key(&mut self) -> Result<&'de str> {
+        let start = self.pos;
+        while self.pos < self.input.len() && !self.input[self.pos..].starts_with("->") {
+            let c = self.peek_char().unwrap();
+            self.pos += c.len_utf8();
+        }
+        if self.pos == start {
+            return Err(self.err_invalid_key());
+        }
+        Ok(&self.input[start..self.pos])
+    }
+
+    fn parse_identifier(&mut self) -> Result<&'de str> {
+        let start = self.pos;
+        while let Some(c) = self.peek_char() {
+            if c.is_alphanumeric() || c == '_' {
+                self.next_char();
+            } else {
+                break;
+            }
+        }
+        if self.pos == start {
+            return Err(self.err_unrecognized());
+        }
+        Ok(&self.input[start..self.pos])
+    }
+
+    fn parse_seq<V: Visitor<'de>>(
+        &mut self,
+        open: char,
+        close: char,
+        visitor: V,
+    ) -> Result<V::Value> {
+        let opening_position = self.line_col();
+        let opening_span = self.current_span();
+        self.expect_char(open)?;
+        let value = visitor.visit_seq(SeqWalker { de: self, close })?;
+        self.skip_separators();
+        if self.peek_char() != Some(close) {
+            let (line, column) = self.line_col();
+            let data = if close == ']' {
+                Iterable::Array
+            } else {
+                Iterable::Tuple
+            };
+            return Err(Error::sequence_kept_open(
+                data,
+                line,
+                column,
+                self.current_span(),
+                opening_position,
+                opening_span,
+            ));
+        }
+        self.next_char();
+        Ok(value)
+    }
+
+    fn parse_map<V: Visitor<'de>>(
+        &mut self,
+        open: char,
+        close: char,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.expect_char(open)?;
+        let value = visitor.visit_map(MapWalker {
+            de: self,
+            close,
+            last_key: None,
+        })?;
+        self.skip_separators();
+        if self.peek_char() != Some(close) {
+            return Err(self.err_unrecognized());
+        }
+        self.next_char();
+        Ok(value)
+    }
+}
+
+pub fn from_str<'de, T: Deserialize<'de>>(s: &'de str) -> Result<T> {
+    let mut deserializer = Deserializer::from_str(s);
+    let value = T::deserialize(&mut deserializer)?;
+    deserializer.skip_separators();
+    if deserializer.pos == deserializer.input.len() {
+        Ok(value)
+    } else {
+        Err(deserializer.err_unrecognized())
+    }
+}
+
+/// Deserializes `s`, tolerating a malformed sequence element or map key
+/// instead of aborting the whole document: the error is recorded (with its
+/// [Span](crate::error::Span)) and the rest of that sequence/map is skipped
+/// up to its closing delimiter, after which parsing resumes one level up.
+/// Later elements or entries of that same sequence/map are dropped along
+/// with the bad one; parsing does not resume at the next entry.
+/// A malformed map or struct *value* is not recovered where it occurs (there
+/// is no way to invent a placeholder of an arbitrary field's type): it fails
+/// the map or struct it belongs to, and that failure is recovered by the
+/// nearest enclosing sequence, if there is one.
+///
+/// Returns the value built from whatever did parse alongside every error
+/// collected along the way; the value is `None` when a failure reached the
+/// top of the document without passing through a sequence. The strict
+/// [from_str] is unaffected by this and still fails on the first error.
+pub fn from_str_recovering<'de, T: Deserialize<'de>>(s: &'de str) -> (Option<T>, Vec<Error>) {
+    let mut deserializer = Deserializer::from_str(s);
+    deserializer.recovering = true;
+    match T::deserialize(&mut deserializer) {
+        Ok(value) => {
+            deserializer.skip_separators();
+            if deserializer.pos != deserializer.input.len() {
+                let err = deserializer.err_unrecognized();
+                deserializer.errors.push(err);
+            }
+            (Some(value), deserializer.errors)
+        }
+        Err(err) => {
+            deserializer.errors.push(err);
+            (None, deserializer.errors)
+        }
+    }
+}
+
+/// Reads all of `reader` and deserializes it, for callers that have an
+/// `io::Read` rather than an already-decoded `&str`. Propagates I/O
+/// failures and invalid UTF-8 as an [Error] via `?`, rather than
+/// stringifying them, with the original error reachable through
+/// [`std::error::Error::source`].
+pub fn from_reader<R: std::io::Read, T: serde::de::DeserializeOwned>(mut reader: R) -> Result<T> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    let text = std::str::from_utf8(&bytes)?;
+    from_str(text)
+}
+
+macro_rules! deserialize_number {
+    ($method:ident, $visit:ident, $ty:ty) => {
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+            self.skip_separators();
+            let token = self.parse_number_token()?;
+            let value: $ty = token.parse().map_err(|_| self.err_invalid_value())?;
+            visitor.$visit(value)
+        }
+    };
+}
+
+impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.mode == Mode::Key {
+            return self.deserialize_str(visitor);
+        }
+        self.skip_separators();
+        match self.peek_char() {
+            Some('t') | Some('f') => self.deserialize_bool(visitor),
+            Some('\'') => self.deserialize_char(visitor),
+            Some('"') => self.deserialize_str(visitor),
+            Some('/') => self.deserialize_unit(visitor),
+            Some('$') => self.deserialize_enum("", &[], visitor),
+            Some('[') => self.deserialize_seq(visitor),
+            Some('(') => self.deserialize_tuple(0, visitor),
+            Some('{') => self.deserialize_map(visitor),
+            Some(c) if c.is_ascii_digit() || c == '-' => {
+                if self.peek_number_is_float() {
+                    self.deserialize_f64(visitor)
+                } else {
+                    self.deserialize_i64(visitor)
+                }
+            }
+            _ => Err(self.err_unrecognized()),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.skip_separators();
+        visitor.visit_bool(self.parse_bool()?)
+    }
+
+    deserialize_number!(deserialize_i8, visit_i8, i8);
+    deserialize_number!(deserialize_i16, visit_i16, i16);
+    deserialize_number!(deserialize_i32, visit_i32, i32);
+    deserialize_number!(deserialize_i64, visit_i64, i64);
+    deserialize_number!(deserialize_u8, visit_u8, u8);
+    deserialize_number!(deserialize_u16, visit_u16, u16);
+    deserialize_number!(deserialize_u32, visit_u32, u32);
+    deserialize_number!(deserialize_u64, visit_u64, u64);
+    deserialize_number!(deserialize_f32, visit_f32, f32);
+    deserialize_number!(deserialize_f64, visit_f64, f64);
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.skip_separators();
+        visitor.visit_char(self.parse_char()?)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.mode == Mode::Key {
+            visitor.visit_borrowed_str(self.parse_bare_key()?)
+        } else {
+            self.skip_separators();
+            visitor.visit_borrowed_str(self.parse_string()?)
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.skip_separators();
+        if self.peek_char() == Some('/') {
+            self.next_char();
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.expect_char('/')?;
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.parse_seq('[', ']', visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        self.parse_seq('(', ')', visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.parse_seq('(', ')', visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.parse_map('{', '}', visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.parse_map('{', '}', visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.expect_char('$')?;
+        visitor.visit_enum(VariantWalker { de: self })
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_any(visitor)
+    }
+}
+
+struct SeqWalker<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    close: char,
+}
+
+impl<'de, 'a> SeqAccess<'de> for SeqWalker<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        self.de.skip_separators();
+        if self.de.peek_char() == Some(self.close) {
+            return Ok(None);
+        }
+        let start = self.de.pos;
+        match seed.deserialize(&mut *self.de) {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if self.de.recovering => {
+                self.de.recover(start, err, self.close);
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+struct MapWalker<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    close: char,
+    last_key: Option<&'de str>,
+}
+
+impl<'de, 'a> MapAccess<'de> for MapWalker<'a, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        self.de.skip_separators();
+        if self.de.peek_char() == Some(self.close) {
+            return Ok(None);
+        }
+        let start = self.de.pos;
+        self.de.mode = Mode::Key;
+        let result = seed.deserialize(&mut *self.de);
+        self.de.mode = Mode::Normal;
+        match result {
+            Ok(value) => {
+                self.last_key = Some(&self.de.input[start..self.de.pos]);
+                Ok(Some(value))
+            }
+            Err(err) if self.de.recovering => {
+                self.de.recover(start, err, self.close);
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        self.de.expect_str("->")?;
+        let key = self.last_key;
+        seed.deserialize(&mut *self.de).map_err(|e| match key {
+            Some(name) => e.field(name),
+            None => e,
+        })
+    }
+}
+
+struct VariantWalker<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+}
+
+impl<'de, 'a> EnumAccess<'de> for VariantWalker<'a, 'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self::Variant)> {
+        let name = self.de.parse_identifier()?;
+        let value = seed.deserialize(name.into_deserializer())?;
+        Ok((value, self))
+    }
+}
+
+impl<'de, 'a> VariantAccess<'de> for VariantWalker<'a, 'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        self.de.expect_char('(')?;
+        let value = seed.deserialize(&mut *self.de)?;
+        self.de.skip_separators();
+        self.de.expect_char(')')?;
+        Ok(value)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        self.de.parse_seq('(', ')', visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.de.parse_map('{', '}', visitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::from_str;
+    use crate::ser::{DelimiterType, PrettyConfig, to_string, to_string_pretty};
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum DistributionMode {
+        Stable,
+        Alpha,
+        Nightly(u8, u8, u8),
+        ReleaseCandidate { changelog: String },
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Plugin {
+        name: String,
+        version: [u8; 3],
+        api_compat: [u8; 3],
+        distribution: DistributionMode,
+        compat: HashMap<String, [u8; 3]>,
+    }
+
+    fn round_trip<T: Serialize + for<'de> Deserialize<'de> + PartialEq + std::fmt::Debug>(
+        value: T,
+    ) {
+        let text = to_string(&value).unwrap();
+        assert_eq!(from_str::<T>(&text).unwrap(), value);
+    }
+
+    #[test]
+    fn round_trips_simple_struct() {
+        round_trip(Plugin {
+            name: "my-plugin".into(),
+            version: [1, 0, 0],
+            api_compat: [1, 0, 1],
+            distribution: DistributionMode::Stable,
+            compat: HashMap::new(),
+        });
+    }
+
+    #[test]
+    fn round_trips_hashmap() {
+        let mut map: HashMap<String, String> = HashMap::new();
+        map.insert("one".into(), "value_one".into());
+        round_trip(map);
+    }
+
+    #[test]
+    fn round_trips_tuple_variant() {
+        round_trip(Plugin {
+            name: "my-plugin".into(),
+            version: [1, 0, 0],
+            api_compat: [2, 0, 0],
+            distribution: DistributionMode::Nightly(12, 13, 25),
+            compat: [("gcc".to_string(), [9, 0, 0])].into(),
+        });
+    }
+
+    #[test]
+    fn round_trips_struct_variant() {
+        round_trip(DistributionMode::ReleaseCandidate {
+            changelog: "Added the switch subcommand".into(),
+        });
+    }
+
+    #[test]
+    fn round_trips_none() {
+        round_trip::<Option<()>>(None);
+    }
+
+    #[test]
+    fn round_trips_whitespace_delimiter() {
+        let tuple: (u8, bool, String) = (1, true, "test".into());
+        let text = to_string_pretty(
+            &tuple,
+            PrettyConfig {
+                delimiter: DelimiterType::Whitespace,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(from_str::<(u8, bool, String)>(&text).unwrap(), tuple);
+    }
+
+    #[test]
+    fn round_trips_hierarchy_pretty() {
+        let data = Plugin {
+            name: "my-plugin".into(),
+            version: [2, 2, 1],
+            api_compat: [2, 0, 0],
+            distribution: DistributionMode::Alpha,
+            compat: HashMap::new(),
+        };
+        let text = to_string_pretty(&data, PrettyConfig::hierarchy()).unwrap();
+        assert_eq!(from_str::<Plugin>(&text).unwrap(), data);
+    }
+
+    #[test]
+    fn reports_position_for_unrecognized_syntax() {
+        let message = from_str::<Plugin>("not even curly")
+            .unwrap_err()
+            .to_string();
+        assert!(
+            message.starts_with("1:"),
+            "expected a line:column prefix: {message}"
+        );
+        assert!(message.contains("syntax could not be properly parsed"));
+    }
+
+    #[test]
+    fn reports_span_for_unrecognized_syntax() {
+        let text = "not even curly";
+        let err = from_str::<Plugin>(text).unwrap_err();
+        let span = err.span().expect("expected a span");
+        assert_eq!(span, crate::error::Span { start: 0, end: 1 });
+        assert_eq!(err.line_column(text), Some((1, 1)));
+    }
+
+    #[test]
+    fn renders_snippet_with_caret_for_unrecognized_syntax() {
+        let text = "not even curly";
+        let err = from_str::<Plugin>(text).unwrap_err();
+        let rendered = err.render(text);
+        assert!(
+            rendered.contains(text),
+            "expected the source line: {rendered}"
+        );
+        assert!(
+            rendered.contains('^'),
+            "expected a caret underline: {rendered}"
+        );
+    }
+
+    #[test]
+    fn renders_opening_note_for_sequence_kept_open() {
+        let text = "(1:0:0";
+        let err = from_str::<[u8; 3]>(text).unwrap_err();
+        let rendered = err.render(text);
+        assert!(
+            rendered.contains("was opened here"),
+            "expected a secondary note: {rendered}"
+        );
+    }
+
+    #[test]
+    fn reports_field_path_for_invalid_value() {
+        let data = Plugin {
+            name: "my-plugin".into(),
+            version: [1, 0, 0],
+            api_compat: [1, 0, 1],
+            distribution: DistributionMode::Stable,
+            compat: HashMap::new(),
+        };
+        let text = to_string(&data).unwrap();
+        let broken = text.replacen("(1:0:0)", "(x:0:0)", 1);
+        let message = from_str::<Plugin>(&broken).unwrap_err().to_string();
+        assert!(
+            message.contains("in field version"),
+            "expected field context in error message: {message}"
+        );
+    }
+
+    #[test]
+    fn from_str_recovering_drops_a_malformed_element_but_keeps_the_rest() {
+        use super::from_str_recovering;
+
+        let (value, errors) =
+            from_str_recovering::<HashMap<String, Vec<u8>>>("{a->[1:2]:b->[bad:3]:c->[4:5]}");
+        let value = value.expect("a partial map should still come through");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(value.get("a"), Some(&vec![1, 2]));
+        assert_eq!(value.get("c"), Some(&vec![4, 5]));
+        assert_eq!(value.get("b"), Some(&Vec::new()));
+    }
+
+    #[test]
+    fn from_str_recovering_matches_from_str_on_valid_input() {
+        use super::from_str_recovering;
+
+        let data = Plugin {
+            name: "my-plugin".into(),
+            version: [1, 0, 0],
+            api_compat: [1, 0, 1],
+            distribution: DistributionMode::Stable,
+            compat: HashMap::new(),
+        };
+        let text = to_string(&data).unwrap();
+        let (value, errors) = from_str_recovering::<Plugin>(&text);
+        assert!(errors.is_empty());
+        assert_eq!(value, Some(data));
+    }
+
+    #[test]
+    fn from_reader_round_trips_through_a_cursor() {
+        use super::from_reader;
+
+        let data = Plugin {
+            name: "my-plugin".into(),
+            version: [1, 0, 0],
+            api_compat: [1, 0, 1],
+            distribution: DistributionMode::Stable,
+            compat: HashMap::new(),
+        };
+        let text = to_string(&data).unwrap();
+        let value: Plugin = from_reader(std::io::Cursor::new(text.as_bytes())).unwrap();
+        assert_eq!(value, data);
+    }
+
+    #[test]
+    fn from_reader_wraps_invalid_utf8_with_a_source() {
+        use super::from_reader;
+        use std::error::Error as _;
+
+        let invalid = [0x66, 0x6f, 0xff, 0x6f];
+        let err = from_reader::<_, Plugin>(std::io::Cursor::new(&invalid[..])).unwrap_err();
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn kind_and_code_identify_the_iterable_left_open() {
+        use crate::{ErrorKind, Iterable};
+
+        let err = from_str::<[u8; 3]>("(1:0:0").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnterminatedSequence(Iterable::Tuple));
+        assert_eq!(err.kind().code(), "E0002");
+    }
+
+    #[test]
+    fn kind_and_code_identify_unrecognized_syntax() {
+        use crate::ErrorKind;
+
+        let err = from_str::<Plugin>("not even curly").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Syntax);
+        assert_eq!(err.kind().code(), "E0001");
+    }
+}
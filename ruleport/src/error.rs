@@ -7,60 +7,288 @@ use crate::Iterable;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// A half-open, absolute byte range `[start, end)` into the original input
+/// naming the offending token, independent of any particular line/column
+/// scheme — the same role `TextRange` plays in rust-analyzer's
+/// `SyntaxError`. Downstream tools (LSP servers, formatters) can slice this
+/// directly instead of re-scanning the input for a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
 #[derive(Debug)]
-pub enum Error {
+enum Repr {
     Message(String),
-    UnrecognizedSyntax(u32, u32),
-    SequenceKeptOpen(Iterable, u32, u32),
-    InvalidKey(u32, u32),
-    InvalidValue(u32, u32),
+    UnrecognizedSyntax,
+    SequenceKeptOpen {
+        data: Iterable,
+        opening_position: (u32, u32),
+        opening_span: Span,
+    },
+    InvalidKey,
+    InvalidValue,
+    Io(std::io::Error),
+    Utf8(std::str::Utf8Error),
+}
+
+/// A coarse, stable category for an [Error], independent of its message
+/// text or any position/span it carries — suitable for matching, mapping
+/// to exit codes, or reporting as an LSP diagnostic code via [Self::code].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Syntax,
+    UnterminatedSequence(Iterable),
+    InvalidKey,
+    InvalidValue,
+    Custom,
+}
+
+impl ErrorKind {
+    /// A stable, version-independent code for this category, e.g. for exit
+    /// codes or LSP diagnostics. These codes are part of the public API and
+    /// will not be renumbered or reused for a different category.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorKind::Syntax => "E0001",
+            ErrorKind::UnterminatedSequence(_) => "E0002",
+            ErrorKind::InvalidKey => "E0003",
+            ErrorKind::InvalidValue => "E0004",
+            ErrorKind::Custom => "E0005",
+        }
+    }
+}
+
+/// An error produced while serializing or deserializing the `ver` format.
+///
+/// Beyond the kind of failure, an `Error` carries the `line:column` it
+/// occurred at (when known), the absolute byte [Span] of the offending
+/// token, and the path of struct/map field names the deserializer had
+/// descended into before the failure bubbled up, so that a caller can
+/// report *where* and *which field* went wrong instead of just *what*.
+#[derive(Debug)]
+pub struct Error {
+    kind: Repr,
+    position: Option<(u32, u32)>,
+    span: Option<Span>,
+    field_path: Vec<String>,
+}
+
+impl Error {
+    /// Builds a leaf error from a static or formatted message, with no
+    /// position, span, or field context attached yet.
+    pub fn message<T: Display>(msg: T) -> Self {
+        Error {
+            kind: Repr::Message(msg.to_string()),
+            position: None,
+            span: None,
+            field_path: Vec::new(),
+        }
+    }
+
+    pub(crate) fn unrecognized_syntax(line: u32, column: u32, span: Span) -> Self {
+        Error {
+            kind: Repr::UnrecognizedSyntax,
+            position: Some((line, column)),
+            span: Some(span),
+            field_path: Vec::new(),
+        }
+    }
+
+    pub(crate) fn sequence_kept_open(
+        data: Iterable,
+        line: u32,
+        column: u32,
+        span: Span,
+        opening_position: (u32, u32),
+        opening_span: Span,
+    ) -> Self {
+        Error {
+            kind: Repr::SequenceKeptOpen {
+                data,
+                opening_position,
+                opening_span,
+            },
+            position: Some((line, column)),
+            span: Some(span),
+            field_path: Vec::new(),
+        }
+    }
+
+    pub(crate) fn invalid_key(line: u32, column: u32, span: Span) -> Self {
+        Error {
+            kind: Repr::InvalidKey,
+            position: Some((line, column)),
+            span: Some(span),
+            field_path: Vec::new(),
+        }
+    }
+
+    pub(crate) fn invalid_value(line: u32, column: u32, span: Span) -> Self {
+        Error {
+            kind: Repr::InvalidValue,
+            position: Some((line, column)),
+            span: Some(span),
+            field_path: Vec::new(),
+        }
+    }
+
+    /// Records that this error unwound out of field `name`, so that the
+    /// outermost caller sees the full path (e.g. `a.b.c`) once the error has
+    /// bubbled all the way up through nested structs and maps.
+    pub fn field<T: Into<String>>(mut self, name: T) -> Self {
+        self.field_path.insert(0, name.into());
+        self
+    }
+
+    /// The absolute byte range of the offending token in the original
+    /// input, if this error carries one.
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+
+    /// This error's coarse, matchable [ErrorKind], independent of its
+    /// message text.
+    pub fn kind(&self) -> ErrorKind {
+        match &self.kind {
+            Repr::Message(_) | Repr::Io(_) | Repr::Utf8(_) => ErrorKind::Custom,
+            Repr::UnrecognizedSyntax => ErrorKind::Syntax,
+            Repr::SequenceKeptOpen { data, .. } => ErrorKind::UnterminatedSequence(*data),
+            Repr::InvalidKey => ErrorKind::InvalidKey,
+            Repr::InvalidValue => ErrorKind::InvalidValue,
+        }
+    }
+
+    /// Computes the `(line, column)` this error's [Span] starts at within
+    /// `source`, by counting newlines up to that byte offset. Unlike the
+    /// `line:column` baked into [Display], this works from just a [Span]
+    /// and the original text, so it stays correct even if the error is
+    /// carried somewhere the original position was never attached.
+    pub fn line_column(&self, source: &str) -> Option<(u32, u32)> {
+        let span = self.span?;
+        let mut line: u32 = 1;
+        let mut column: u32 = 1;
+        for c in source[..span.start.min(source.len())].chars() {
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        Some((line, column))
+    }
+
+    /// Renders a compiler-style diagnostic: the offending line of `source`
+    /// with a line-number gutter, a caret/tilde underline under the failing
+    /// span, and this error's message. For a [sequence kept open
+    /// error](Self::sequence_kept_open), a secondary note also points back
+    /// at the opening delimiter so the reader can see what was left
+    /// unterminated. This is purely additive over [Display], which stays
+    /// terse for `{}`.
+    pub fn render(&self, source: &str) -> String {
+        let mut output = String::new();
+        if let (Some((line, column)), Some(span)) = (self.position, self.span) {
+            output.push_str(&render_snippet(source, line, column, span));
+            output.push('\n');
+        }
+        output.push_str(&self.to_string());
+        if let Repr::SequenceKeptOpen {
+            data,
+            opening_position: (line, column),
+            opening_span,
+        } = &self.kind
+        {
+            output.push_str(&format!("\nnote: the {} was opened here\n", data));
+            output.push_str(&render_snippet(source, *line, *column, *opening_span));
+        }
+        output
+    }
+}
+
+/// Extracts the line `source` reports `line` as, and renders it beneath a
+/// gutter with a `^`/`~` underline starting at `column` and spanning the
+/// character width of `span`.
+fn render_snippet(source: &str, line: u32, column: u32, span: Span) -> String {
+    let text = source
+        .lines()
+        .nth(line.saturating_sub(1) as usize)
+        .unwrap_or("");
+    let gutter = format!("{} | ", line);
+    let width = source
+        .get(span.start..span.end)
+        .map(|s| s.chars().count())
+        .unwrap_or(1)
+        .max(1);
+    let underline = format!("^{}", "~".repeat(width - 1));
+    let pad = " ".repeat(gutter.len() + column.saturating_sub(1) as usize);
+    format!("{}{}\n{}{}", gutter, text, pad, underline)
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error {
+            kind: Repr::Io(err),
+            position: None,
+            span: None,
+            field_path: Vec::new(),
+        }
+    }
+}
+
+impl From<std::str::Utf8Error> for Error {
+    fn from(err: std::str::Utf8Error) -> Self {
+        Error {
+            kind: Repr::Utf8(err),
+            position: None,
+            span: None,
+            field_path: Vec::new(),
+        }
+    }
 }
 
 impl ser::Error for Error {
     fn custom<T: Display>(msg: T) -> Self {
-        Error::Message(msg.to_string())
+        Error::message(msg)
     }
 }
 
 impl de::Error for Error {
     fn custom<T: Display>(msg: T) -> Self {
-        Error::Message(msg.to_string())
+        Error::message(msg)
     }
 }
 
-macro_rules! format_as_str {
-    ($($arg:tt)*) => {
-        format!($($arg)*).as_str()
-    };
-}
-
 impl Display for Error {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Error::Message(msg) => formatter.write_str(msg),
-            Error::UnrecognizedSyntax(line, column) => formatter.write_str(format_as_str!(
-                "syntax could not be properly parsed ({}:{})",
-                line,
-                column
-            )),
-            Error::SequenceKeptOpen(data, line, column) => formatter.write_str(format_as_str!(
-                "the {} at location ({}:{}) was kept open",
-                data,
-                line,
-                column
-            )),
-            Error::InvalidKey(line, column) => formatter.write_str(format_as_str!(
-                "key at location ({}:{}) is invalid",
-                line,
-                column
-            )),
-            Error::InvalidValue(line, column) => formatter.write_str(format_as_str!(
-                "value at location ({}:{}) was invalid",
-                line,
-                column
-            )),
+        if let Some((line, column)) = self.position {
+            write!(formatter, "{}:{}: ", line, column)?;
+        }
+        match &self.kind {
+            Repr::Message(msg) => formatter.write_str(msg)?,
+            Repr::UnrecognizedSyntax => {
+                formatter.write_str("syntax could not be properly parsed")?
+            }
+            Repr::SequenceKeptOpen { data, .. } => write!(formatter, "the {} was kept open", data)?,
+            Repr::InvalidKey => formatter.write_str("key is invalid")?,
+            Repr::InvalidValue => formatter.write_str("value is invalid")?,
+            Repr::Io(err) => write!(formatter, "I/O error: {}", err)?,
+            Repr::Utf8(err) => write!(formatter, "invalid UTF-8: {}", err)?,
+        }
+        if !self.field_path.is_empty() {
+            write!(formatter, " (in field {})", self.field_path.join("."))?;
         }
+        Ok(())
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            Repr::Io(err) => Some(err),
+            Repr::Utf8(err) => Some(err),
+            _ => None,
+        }
+    }
+}
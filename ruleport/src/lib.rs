@@ -1,14 +1,15 @@
 pub mod de;
 pub mod error;
 pub mod ser;
+pub mod transcode;
 
 use std::fmt::{self, Display, Formatter};
 
-// pub use de::{Deserializer, from_str};
-pub use error::{Error, Result};
+pub use de::{Deserializer, from_reader, from_str, from_str_recovering};
+pub use error::{Error, ErrorKind, Result};
 // pub use ser::{Serializer, to_string};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Iterable {
     Tuple,
     Array,
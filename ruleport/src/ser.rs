@@ -1,6 +1,8 @@
 use crate::error::{Error, Result};
 use concat_idents::concat_idents;
 use serde::{Serialize, ser};
+use std::fmt;
+use std::io;
 
 #[derive(PartialEq)]
 enum SerializerMode {
@@ -40,11 +42,46 @@ impl PrettyConfig {
     }
 }
 
-pub struct Serializer {
-    output: String,
+pub struct Serializer<'w, W: fmt::Write> {
+    writer: &'w mut W,
     mode: SerializerMode,
     pretty: PrettyConfig,
     indent_tracking: u16,
+    last_char: Option<char>,
+}
+
+/// Adapts an [io::Write] sink (a file, a socket, stdout) into the
+/// [fmt::Write] that [Serializer] writes through, so `ver` output can be
+/// streamed straight to byte-oriented destinations without an intermediate
+/// `String`.
+pub struct IoWriteAdapter<'w, W: io::Write> {
+    inner: &'w mut W,
+}
+
+impl<'w, W: io::Write> IoWriteAdapter<'w, W> {
+    pub fn new(inner: &'w mut W) -> IoWriteAdapter<'w, W> {
+        IoWriteAdapter { inner }
+    }
+}
+
+impl<'w, W: io::Write> fmt::Write for IoWriteAdapter<'w, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|_| fmt::Error)
+    }
+}
+
+impl<'w, W: fmt::Write> Serializer<'w, W> {
+    /// Writes `s` to the underlying sink, tracking its last character so the
+    /// `ends_with` checks scattered through this module don't need to read
+    /// back from the sink.
+    fn write(&mut self, s: &str) -> Result<()> {
+        if let Some(c) = s.chars().next_back() {
+            self.last_char = Some(c);
+        }
+        self.writer
+            .write_str(s)
+            .map_err(|e| <Error as ser::Error>::custom(e))
+    }
 }
 
 macro_rules! write_types {
@@ -63,33 +100,41 @@ macro_rules! write_types {
 }
 
 pub fn to_string<T: Serialize>(value: &T) -> Result<String> {
-    let mut serializer: Serializer = Serializer {
-        output: String::new(),
-        mode: SerializerMode::Normal,
-        pretty: PrettyConfig::default(),
-        indent_tracking: 0,
-    };
-    value.serialize(&mut serializer)?;
-    Ok(serializer.output)
+    let mut output: String = String::new();
+    to_writer(&mut output, value)?;
+    Ok(output)
 }
 
 pub fn to_string_pretty<T: Serialize>(value: &T, pretty: PrettyConfig) -> Result<String> {
-    let mut serializer: Serializer = Serializer {
-        output: String::new(),
+    let mut output: String = String::new();
+    to_writer_pretty(&mut output, value, pretty)?;
+    Ok(output)
+}
+
+pub fn to_writer<W: fmt::Write, T: Serialize>(writer: &mut W, value: &T) -> Result<()> {
+    to_writer_pretty(writer, value, PrettyConfig::default())
+}
+
+pub fn to_writer_pretty<W: fmt::Write, T: Serialize>(
+    writer: &mut W,
+    value: &T,
+    pretty: PrettyConfig,
+) -> Result<()> {
+    let mut serializer: Serializer<'_, W> = Serializer {
+        writer,
         mode: SerializerMode::Normal,
         pretty,
         indent_tracking: 0,
+        last_char: None,
     };
-    value.serialize(&mut serializer)?;
-    Ok(serializer.output)
+    value.serialize(&mut serializer)
 }
 
-impl ser::Serializer for &mut Serializer {
+impl<'w, W: fmt::Write> ser::Serializer for &mut Serializer<'w, W> {
     write_types!();
 
     fn serialize_bool(self, v: bool) -> Result<()> {
-        self.output += if v { "true" } else { "false" };
-        Ok(())
+        self.write(if v { "true" } else { "false" })
     }
 
     fn serialize_i8(self, v: i8) -> Result<()> {
@@ -106,8 +151,7 @@ impl ser::Serializer for &mut Serializer {
 
     fn serialize_i64(self, v: i64) -> Result<()> {
         let mut buffer: itoa::Buffer = itoa::Buffer::new();
-        self.output += buffer.format(v);
-        Ok(())
+        self.write(buffer.format(v))
     }
 
     fn serialize_u8(self, v: u8) -> Result<()> {
@@ -124,8 +168,7 @@ impl ser::Serializer for &mut Serializer {
 
     fn serialize_u64(self, v: u64) -> Result<()> {
         let mut buffer: itoa::Buffer = itoa::Buffer::new();
-        self.output += buffer.format(v);
-        Ok(())
+        self.write(buffer.format(v))
     }
 
     fn serialize_f32(self, v: f32) -> Result<()> {
@@ -134,27 +177,24 @@ impl ser::Serializer for &mut Serializer {
 
     fn serialize_f64(self, v: f64) -> Result<()> {
         let mut buffer: ryu::Buffer = ryu::Buffer::new();
-        self.output += buffer.format(v);
-        Ok(())
+        self.write(buffer.format(v))
     }
 
     fn serialize_char(self, v: char) -> Result<()> {
-        self.output += format!("'{v}'").as_str();
-        Ok(())
+        self.write(format!("'{v}'").as_str())
     }
 
     fn serialize_str(self, v: &str) -> Result<()> {
         if self.mode == SerializerMode::Key {
-            self.output += v;
+            self.write(v)
         } else {
-            self.output += format!("\"{v}\"").as_str();
+            self.write(format!("\"{v}\"").as_str())
         }
-        Ok(())
     }
 
     fn serialize_bytes(self, v: &[u8]) -> Result<()> {
         use serde::ser::SerializeSeq;
-        let mut seq: &mut Serializer = self.serialize_seq(Some(v.len()))?;
+        let mut seq: &mut Serializer<'w, W> = self.serialize_seq(Some(v.len()))?;
         for byte in v {
             seq.serialize_element(byte)?;
         }
@@ -170,8 +210,7 @@ impl ser::Serializer for &mut Serializer {
     }
 
     fn serialize_unit(self) -> Result<()> {
-        self.output += "/";
-        Ok(())
+        self.write("/")
     }
 
     fn serialize_unit_struct(self, _: &'static str) -> Result<()> {
@@ -184,8 +223,7 @@ impl ser::Serializer for &mut Serializer {
         _variant_index: u32,
         variant: &'static str,
     ) -> Result<()> {
-        self.output += format!("${}", variant).as_str();
-        Ok(())
+        self.write(format!("${}", variant).as_str())
     }
 
     fn serialize_newtype_struct<T: ?Sized + Serialize>(
@@ -203,19 +241,18 @@ impl ser::Serializer for &mut Serializer {
         variant: &'static str,
         value: &T,
     ) -> Result<()> {
-        self.output += format!("${}(", variant).as_str();
+        self.write(format!("${}(", variant).as_str())?;
         value.serialize(&mut *self)?;
-        self.output += ")";
-        Ok(())
+        self.write(")")
     }
 
     fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
-        self.output += "[";
+        self.write("[")?;
         Ok(self)
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
-        self.output += "(";
+        self.write("(")?;
         Ok(self)
     }
 
@@ -234,13 +271,13 @@ impl ser::Serializer for &mut Serializer {
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        self.output += format!("${}", variant).as_str();
-        self.output += "(";
+        self.write(format!("${}", variant).as_str())?;
+        self.write("(")?;
         Ok(self)
     }
 
     fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
-        self.output += "{";
+        self.write("{")?;
         self.mode = SerializerMode::Map;
         self.indent_tracking += 1;
         Ok(self)
@@ -257,7 +294,7 @@ impl ser::Serializer for &mut Serializer {
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        self.output += format!("${}{{", variant).as_str();
+        self.write(format!("${}{{", variant).as_str())?;
         Ok(self)
     }
 }
@@ -279,37 +316,36 @@ macro_rules! serialize_iterable {
 
         concat_idents!(fn_name = serialize_, $suffix {
             fn fn_name<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
-                if !self.output.ends_with($start) {
+                if self.last_char != Some($start.as_bytes()[0] as char) {
                     let mut char_data: &str = delimiter!(self);
                     if ($start == "(" || $start == "[") && char_data == "\n" {
                         char_data = " ";
                     }
-                    self.output += char_data;
+                    self.write(char_data)?;
                 }
                 value.serialize(&mut **self)
             }
         });
 
         fn end(self) -> Result<()> {
-            self.output += $end;
-            Ok(())
+            self.write($end)
         }
     };
 }
 
-impl ser::SerializeSeq for &mut Serializer {
+impl<'w, W: fmt::Write> ser::SerializeSeq for &mut Serializer<'w, W> {
     serialize_iterable!("[", "]", element);
 }
 
-impl ser::SerializeTuple for &mut Serializer {
+impl<'w, W: fmt::Write> ser::SerializeTuple for &mut Serializer<'w, W> {
     serialize_iterable!("(", ")", element);
 }
 
-impl ser::SerializeTupleStruct for &mut Serializer {
+impl<'w, W: fmt::Write> ser::SerializeTupleStruct for &mut Serializer<'w, W> {
     serialize_iterable!("(", ")", field);
 }
 
-impl ser::SerializeTupleVariant for &mut Serializer {
+impl<'w, W: fmt::Write> ser::SerializeTupleVariant for &mut Serializer<'w, W> {
     serialize_iterable!("(", ")", field);
 }
 
@@ -321,7 +357,7 @@ macro_rules! serialize_key {
     };
 }
 
-fn create_indent(serializer: &mut Serializer) -> String {
+fn create_indent<W: fmt::Write>(serializer: &mut Serializer<'_, W>) -> String {
     let mut indent: String = String::new();
     for _ in 0..serializer.pretty.indent_width as u16 * serializer.indent_tracking {
         indent.push(' ');
@@ -333,19 +369,18 @@ macro_rules! indents {
     ($x: expr) => {
         if $x.pretty.delimiter == DelimiterType::Newline {
             let indent_string: String = create_indent($x);
-            let indent: &str = indent_string.as_str();
-            $x.output += indent;
+            $x.write(indent_string.as_str())?;
         }
     };
 }
 
-impl ser::SerializeMap for &mut Serializer {
+impl<'w, W: fmt::Write> ser::SerializeMap for &mut Serializer<'w, W> {
     type Ok = ();
     type Error = Error;
 
     fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
-        if !self.output.ends_with('{') || self.pretty.delimiter == DelimiterType::Newline {
-            self.output += delimiter!(self);
+        if self.last_char != Some('{') || self.pretty.delimiter == DelimiterType::Newline {
+            self.write(delimiter!(self))?;
         }
         self.mode = SerializerMode::Key;
         indents!(self);
@@ -354,7 +389,7 @@ impl ser::SerializeMap for &mut Serializer {
 
     fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
         self.mode = SerializerMode::Normal;
-        self.output += "->";
+        self.write("->")?;
         value.serialize(&mut **self)
     }
 
@@ -367,13 +402,13 @@ impl ser::SerializeMap for &mut Serializer {
         } else {
             format!("\n{}}}", create_indent(self))
         };
-        self.output += ending.as_str();
+        self.write(ending.as_str())?;
         self.mode = SerializerMode::Normal;
         Ok(())
     }
 }
 
-impl ser::SerializeStruct for &mut Serializer {
+impl<'w, W: fmt::Write> ser::SerializeStruct for &mut Serializer<'w, W> {
     type Ok = ();
     type Error = Error;
 
@@ -381,12 +416,12 @@ impl ser::SerializeStruct for &mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        if !self.output.ends_with('{') || self.pretty.delimiter == DelimiterType::Newline {
-            self.output += delimiter!(self);
+        if self.last_char != Some('{') || self.pretty.delimiter == DelimiterType::Newline {
+            self.write(delimiter!(self))?;
         }
         indents!(self);
         serialize_key!(self, key);
-        self.output += "->";
+        self.write("->")?;
         value.serialize(&mut **self)
     }
 
@@ -399,13 +434,13 @@ impl ser::SerializeStruct for &mut Serializer {
         } else {
             format!("\n{}}}", create_indent(self))
         };
-        self.output += ending.as_str();
+        self.write(ending.as_str())?;
         self.mode = SerializerMode::Normal;
         Ok(())
     }
 }
 
-impl ser::SerializeStructVariant for &mut Serializer {
+impl<'w, W: fmt::Write> ser::SerializeStructVariant for &mut Serializer<'w, W> {
     type Ok = ();
     type Error = Error;
 
@@ -413,17 +448,16 @@ impl ser::SerializeStructVariant for &mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        if !self.output.ends_with('{') {
-            self.output += delimiter!(self);
+        if self.last_char != Some('{') {
+            self.write(delimiter!(self))?;
         }
         serialize_key!(self, key);
-        self.output += "->";
+        self.write("->")?;
         value.serialize(&mut **self)
     }
 
     fn end(self) -> Result<()> {
-        self.output += "}";
-        Ok(())
+        self.write("}")
     }
 }
 
@@ -533,4 +567,32 @@ mod test {
         };
         println!("{}", to_string(&distribution).unwrap());
     }
+
+    #[test]
+    fn to_writer_matches_to_string() {
+        let data: Plugin = Plugin {
+            name: "my-plugin".into(),
+            version: [1, 0, 0],
+            api_compat: [1, 0, 1],
+            distribution: DistributionMode::Stable,
+            compat: HashMap::new(),
+        };
+        let mut streamed: String = String::new();
+        to_writer(&mut streamed, &data).unwrap();
+        assert_eq!(streamed, to_string(&data).unwrap());
+    }
+
+    #[test]
+    fn to_writer_streams_through_an_io_sink() {
+        let data: Plugin = Plugin {
+            name: "my-plugin".into(),
+            version: [1, 0, 0],
+            api_compat: [1, 0, 1],
+            distribution: DistributionMode::Stable,
+            compat: HashMap::new(),
+        };
+        let mut bytes: Vec<u8> = Vec::new();
+        to_writer(&mut IoWriteAdapter::new(&mut bytes), &data).unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), to_string(&data).unwrap());
+    }
 }
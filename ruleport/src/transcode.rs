@@ -0,0 +1,268 @@
+//! Converts a document between a concrete serde-compatible source format and
+//! the `ver` format without collecting it into a fixed intermediate type
+//! first: the source [Deserializer](de::Deserializer) is driven through
+//! exactly one [Serialize] call, whose `visit_*` methods forward straight
+//! onto the target [Serializer](ser::Serializer) — the same trick used by
+//! comparable transcode helpers (e.g. `serde-transcode`).
+//!
+//! Transcoding an enum-shaped value through this untyped path isn't
+//! supported: choosing which `VariantAccess` method to call requires already
+//! knowing the target type's shape, which a blind visitor never has. This is
+//! the same limitation comparable transcode crates document.
+
+use crate::error::{Error, Result};
+use crate::ser::to_writer;
+use serde::de::{
+    self, DeserializeSeed, Deserializer as SerdeDeserializer, MapAccess, SeqAccess, Visitor,
+};
+use serde::ser::{self, Serialize, SerializeMap, SerializeSeq, Serializer as SerdeSerializer};
+use std::cell::RefCell;
+use std::fmt::{self, Display};
+
+/// Feeds `deserializer` directly into `serializer`, without collecting the
+/// document into an intermediate typed value first.
+pub fn transcode<'de, D, S>(deserializer: D, serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    D: SerdeDeserializer<'de>,
+    D::Error: Display,
+    S: SerdeSerializer,
+{
+    Transcoder {
+        de: RefCell::new(Some(deserializer)),
+    }
+    .serialize(serializer)
+}
+
+struct Transcoder<D> {
+    de: RefCell<Option<D>>,
+}
+
+impl<'de, D> Serialize for Transcoder<D>
+where
+    D: SerdeDeserializer<'de>,
+    D::Error: Display,
+{
+    fn serialize<S: SerdeSerializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let de = self
+            .de
+            .borrow_mut()
+            .take()
+            .expect("transcoder driven twice");
+        de.deserialize_any(TranscodeVisitor { serializer })
+            .map_err(|err| <S::Error as ser::Error>::custom(err))
+    }
+}
+
+struct TranscodeVisitor<S> {
+    serializer: S,
+}
+
+impl<'de, S: SerdeSerializer> Visitor<'de> for TranscodeVisitor<S> {
+    type Value = S::Ok;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any value representable in the source format")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> std::result::Result<Self::Value, E> {
+        self.serializer.serialize_bool(v).map_err(ser_err)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> std::result::Result<Self::Value, E> {
+        self.serializer.serialize_i64(v).map_err(ser_err)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> std::result::Result<Self::Value, E> {
+        self.serializer.serialize_u64(v).map_err(ser_err)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> std::result::Result<Self::Value, E> {
+        self.serializer.serialize_f64(v).map_err(ser_err)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<Self::Value, E> {
+        self.serializer.serialize_str(v).map_err(ser_err)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> std::result::Result<Self::Value, E> {
+        self.serializer.serialize_bytes(v).map_err(ser_err)
+    }
+
+    fn visit_none<E: de::Error>(self) -> std::result::Result<Self::Value, E> {
+        self.serializer.serialize_none().map_err(ser_err)
+    }
+
+    fn visit_some<D: SerdeDeserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Self::Value, D::Error> {
+        self.serializer
+            .serialize_some(&Transcoder {
+                de: RefCell::new(Some(deserializer)),
+            })
+            .map_err(ser_err)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> std::result::Result<Self::Value, E> {
+        self.serializer.serialize_unit().map_err(ser_err)
+    }
+
+    fn visit_newtype_struct<D: SerdeDeserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Self::Value, D::Error> {
+        Transcoder {
+            de: RefCell::new(Some(deserializer)),
+        }
+        .serialize(self.serializer)
+        .map_err(ser_err)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(
+        self,
+        mut seq: A,
+    ) -> std::result::Result<Self::Value, A::Error> {
+        let mut serialize_seq = self
+            .serializer
+            .serialize_seq(seq.size_hint())
+            .map_err(ser_err)?;
+        while seq
+            .next_element_seed(SeqElementSeed {
+                serialize_seq: &mut serialize_seq,
+            })?
+            .is_some()
+        {}
+        serialize_seq.end().map_err(ser_err)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> std::result::Result<Self::Value, A::Error> {
+        let mut serialize_map = self
+            .serializer
+            .serialize_map(map.size_hint())
+            .map_err(ser_err)?;
+        while map
+            .next_key_seed(MapKeySeed {
+                serialize_map: &mut serialize_map,
+            })?
+            .is_some()
+        {
+            map.next_value_seed(MapValueSeed {
+                serialize_map: &mut serialize_map,
+            })?;
+        }
+        serialize_map.end().map_err(ser_err)
+    }
+}
+
+struct SeqElementSeed<'a, T> {
+    serialize_seq: &'a mut T,
+}
+
+impl<'de, 'a, T: SerializeSeq> DeserializeSeed<'de> for SeqElementSeed<'a, T> {
+    type Value = ();
+
+    fn deserialize<D: SerdeDeserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Self::Value, D::Error> {
+        self.serialize_seq
+            .serialize_element(&Transcoder {
+                de: RefCell::new(Some(deserializer)),
+            })
+            .map_err(ser_err)
+    }
+}
+
+struct MapKeySeed<'a, T> {
+    serialize_map: &'a mut T,
+}
+
+impl<'de, 'a, T: SerializeMap> DeserializeSeed<'de> for MapKeySeed<'a, T> {
+    type Value = ();
+
+    fn deserialize<D: SerdeDeserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Self::Value, D::Error> {
+        self.serialize_map
+            .serialize_key(&Transcoder {
+                de: RefCell::new(Some(deserializer)),
+            })
+            .map_err(ser_err)
+    }
+}
+
+struct MapValueSeed<'a, T> {
+    serialize_map: &'a mut T,
+}
+
+impl<'de, 'a, T: SerializeMap> DeserializeSeed<'de> for MapValueSeed<'a, T> {
+    type Value = ();
+
+    fn deserialize<D: SerdeDeserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<Self::Value, D::Error> {
+        self.serialize_map
+            .serialize_value(&Transcoder {
+                de: RefCell::new(Some(deserializer)),
+            })
+            .map_err(ser_err)
+    }
+}
+
+/// Converts a target serializer's error into the source deserializer's error
+/// domain, since a single `visit_*`/`serialize_*` call has to return one
+/// concrete error type but drives both.
+fn ser_err<E1: de::Error, E2: ser::Error>(err: E2) -> E1 {
+    E1::custom(err)
+}
+
+/// Converts a RON document into the `ver` format.
+pub fn from_ron(data: &str) -> Result<String> {
+    let mut deserializer = ron::de::Deserializer::from_str(data).map_err(Error::message)?;
+    let mut output: String = String::new();
+    to_writer(
+        &mut output,
+        &Transcoder {
+            de: RefCell::new(Some(&mut deserializer)),
+        },
+    )?;
+    Ok(output)
+}
+
+/// Converts a `ver`-format document into RON.
+pub fn to_ron(data: &str) -> Result<String> {
+    let mut deserializer = crate::de::Deserializer::from_str(data);
+    let mut output: String = String::new();
+    {
+        let mut serializer = ron::Serializer::new(&mut output, None).map_err(Error::message)?;
+        transcode(&mut deserializer, &mut serializer).map_err(Error::message)?;
+    }
+    Ok(output)
+}
+
+/// Converts a JSON document into the `ver` format.
+pub fn from_json(data: &str) -> Result<String> {
+    let mut deserializer = serde_json::Deserializer::from_str(data);
+    let mut output: String = String::new();
+    to_writer(
+        &mut output,
+        &Transcoder {
+            de: RefCell::new(Some(&mut deserializer)),
+        },
+    )?;
+    Ok(output)
+}
+
+/// Converts a `ver`-format document into JSON.
+pub fn to_json(data: &str) -> Result<String> {
+    let mut deserializer = crate::de::Deserializer::from_str(data);
+    let mut bytes: Vec<u8> = Vec::new();
+    let mut serializer = serde_json::Serializer::new(&mut bytes);
+    transcode(&mut deserializer, &mut serializer).map_err(Error::message)?;
+    String::from_utf8(bytes).map_err(Error::message)
+}
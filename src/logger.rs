@@ -0,0 +1,79 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::env;
+
+/// Output format for log lines emitted by [Logger].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `binary: message`, meant for a human reading a terminal.
+    Human,
+    /// `level\tmodule\tmessage`, meant for scripts to filter by severity.
+    Line,
+}
+
+/// The ordered set of levels that `-v`/`-q` step through, from quietest to
+/// loudest.
+const LEVELS: [LevelFilter; 6] = [
+    LevelFilter::Off,
+    LevelFilter::Error,
+    LevelFilter::Warn,
+    LevelFilter::Info,
+    LevelFilter::Debug,
+    LevelFilter::Trace,
+];
+
+/// Resolves the effective log level from the `VER_LOG` environment variable
+/// (defaulting to [LevelFilter::Info]), shifted up by `verbose` steps and
+/// down by `quiet` steps.
+pub fn resolve_level(verbose: u8, quiet: u8) -> LevelFilter {
+    let base: LevelFilter = env::var("VER_LOG")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(LevelFilter::Info);
+    let base_index: usize = LEVELS.iter().position(|level| *level == base).unwrap_or(3);
+    let shift: i16 = verbose as i16 - quiet as i16;
+    let index: usize = (base_index as i16 + shift).clamp(0, LEVELS.len() as i16 - 1) as usize;
+    LEVELS[index]
+}
+
+/// A minimal logger that routes info-and-below to stdout and warn-and-above
+/// to stderr, in either a human-readable or a structured line format.
+pub struct Logger {
+    format: LogFormat,
+}
+
+impl Logger {
+    /// Installs a [Logger] as the global logger, bounding emitted records to
+    /// `level`.
+    pub fn init(level: LevelFilter, format: LogFormat) {
+        log::set_boxed_logger(Box::new(Logger { format })).expect("a logger was already installed");
+        log::set_max_level(level);
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line: String = match self.format {
+            LogFormat::Human => format!("{}: {}", env!("CARGO_BIN_NAME"), record.args()),
+            LogFormat::Line => format!(
+                "{}\t{}\t{}",
+                record.level(),
+                record.module_path().unwrap_or("?"),
+                record.args()
+            ),
+        };
+        if record.level() <= Level::Warn {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    fn flush(&self) {}
+}
@@ -1,17 +1,124 @@
 use clap::{Arg, ArgAction, ArgMatches, Command, arg, command, value_parser};
 use libver::*;
+use log::LevelFilter;
+use logger::{LogFormat, Logger, resolve_level};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     env,
     fs::write,
+    io::IsTerminal,
     path::PathBuf,
     process::{Stdio, exit},
+    time::Duration,
 };
 
-fn handle_commands() -> ArgMatches {
+mod logger;
+
+/// The subcommands built into the CLI itself; user-defined aliases may not
+/// shadow any of these.
+const BUILTIN_SUBCOMMANDS: [&str; 6] = ["check", "apply", "switch", "scope", "template", "help"];
+
+/// Flags that consume one or more following tokens, paired with how many
+/// values each occurrence consumes, so [find_subcommand_token] can skip past
+/// them without a full clap parse.
+const VALUE_FLAGS: [(&str, usize); 10] = [
+    ("-c", 1),
+    ("--config", 1),
+    ("-r", 2),
+    ("--replace", 2),
+    ("-o", 1),
+    ("--overlay", 1),
+    ("-s", 1),
+    ("--set", 1),
+    ("--log-format", 1),
+    ("--timeout", 1),
+];
+
+/// Finds the index, within raw CLI arguments, of the first token that isn't
+/// a flag or a flag's value — i.e. the subcommand position, whether it names
+/// a built-in subcommand or a user-defined alias.
+fn find_subcommand_token(args: &[String]) -> Option<usize> {
+    let mut i: usize = 1;
+    while i < args.len() {
+        let arg: &str = args[i].as_str();
+        if let Some((_, values)) = VALUE_FLAGS.iter().find(|(flag, _)| *flag == arg) {
+            i += 1 + values;
+        } else if arg.starts_with('-') {
+            i += 1;
+        } else {
+            return Some(i);
+        }
+    }
+    None
+}
+
+/// Splices a user-defined alias at `args`'s subcommand position into its
+/// stored expansion, repeating until the leading token is a built-in
+/// subcommand or isn't a known alias. Returns an error message if an alias
+/// expands into itself or a cycle.
+fn expand_aliases(
+    mut args: Vec<String>,
+    aliases: &HashMap<String, String>,
+) -> Result<Vec<String>, String> {
+    let Some(idx) = find_subcommand_token(&args) else {
+        return Ok(args);
+    };
+    let mut expanded: HashSet<String> = HashSet::new();
+    loop {
+        let token: String = args[idx].clone();
+        if BUILTIN_SUBCOMMANDS.contains(&token.as_str()) {
+            return Ok(args);
+        }
+        let Some(expansion) = aliases.get(&token) else {
+            return Ok(args);
+        };
+        if !expanded.insert(token.clone()) {
+            return Err(format!(
+                "alias \"{}\" expands into a cycle and cannot be resolved",
+                token
+            ));
+        }
+        let replacement: Vec<String> = expansion.split_whitespace().map(String::from).collect();
+        args.splice(idx..idx + 1, replacement);
+    }
+}
+
+/// Scans raw CLI arguments for a `-c`/`--config` value without invoking clap,
+/// mirroring the fallback chain used for `config_path` in `main`. Needed to
+/// locate the alias table before the subcommand can be resolved.
+fn prescan_config_path(args: &[String]) -> PathBuf {
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if (arg == "-c" || arg == "--config")
+            && let Some(value) = iter.next()
+        {
+            return value.into();
+        }
+    }
+    if let Ok(file) = env::var("VER_CONFIG") {
+        file.into()
+    } else {
+        ".ver.ron".into()
+    }
+}
+
+/// Prints `status`'s message to stdout or stderr as appropriate, then exits
+/// the process with its numeric code.
+fn report_and_exit((code, message): (ExitCode, String)) -> ! {
+    if code == ExitCode::Success {
+        if !message.is_empty() {
+            println!("{}: {}", env!("CARGO_BIN_NAME"), message);
+        }
+    } else if code.code() != 0 {
+        eprintln!("{}: {}", env!("CARGO_BIN_NAME"), message);
+    }
+    exit(code.code());
+}
+
+fn handle_commands(args: Vec<String>) -> ArgMatches {
     command!()
         .arg(
-            arg!(-c --config <FILE> "The configuration to use")
+            arg!(-c --config <FILE> "The configuration to use; \"-\" reads from stdin")
                 .required(false)
                 .value_parser(value_parser!(PathBuf))
         )
@@ -28,12 +135,72 @@ fn handle_commands() -> ArgMatches {
                 .action(ArgAction::Append)
                 .value_parser(value_parser!(PathBuf))
         )
+        .arg(
+            Arg::new("set")
+                .short('s')
+                .long("set")
+                .help("Override a runtime's version for this invocation, without touching the configuration file")
+                .required(false)
+                .action(ArgAction::Append)
+                .global(true)
+                .number_of_values(1)
+                .value_name("RUNTIME=VERSION")
+        )
+        .arg(
+            Arg::new("timeout")
+                .long("timeout")
+                .help("The maximum time, in milliseconds, to wait on any external process")
+                .required(false)
+                .global(true)
+                .value_parser(value_parser!(u64))
+                .value_name("MILLISECONDS")
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .help("Increase logging verbosity; may be repeated")
+                .required(false)
+                .global(true)
+                .action(ArgAction::Count)
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .help("Decrease logging verbosity; may be repeated")
+                .required(false)
+                .global(true)
+                .action(ArgAction::Count)
+        )
+        .arg(
+            Arg::new("log-format")
+                .long("log-format")
+                .help("The format to emit log messages in")
+                .required(false)
+                .global(true)
+                .value_parser(["human", "line"])
+                .default_value("human")
+        )
         .subcommand(
             Command::new("check")
                 .about("Checks all runtime versions for their existence")
                 .long_about(
                     "This subcommand performs generic version resolution, and then \
-                    identifies whether or not all of the runtime versions actually exist.",
+                    identifies whether or not all of the runtime versions actually exist.\n\n\
+                    Results are cached in a .ver.cache.ron file next to the configuration, \
+                    keyed on each runtime's version directory, its search paths, and the merged \
+                    configuration; a runtime whose fingerprint still matches the cache is reported \
+                    as installed without running the timed version check again. Computing the \
+                    fingerprint still reads the metadata of the version directory and its search \
+                    paths from the filesystem. Use --no-cache to always validate for real.",
+                )
+                .arg(
+                    Arg::new("no-cache")
+                        .long("no-cache")
+                        .visible_alias("force")
+                        .help("Bypass the fingerprint cache and always validate against the filesystem")
+                        .action(ArgAction::SetTrue),
                 ),
         )
         .subcommand(
@@ -46,7 +213,9 @@ fn handle_commands() -> ArgMatches {
                     flag.\n\n\
                     Note that this subcommand by default will operate on a copy of the configuration \
                     made before applying any overlays. To apply the changes made by overlays as well, \
-                    use the --full flag.",
+                    use the --full flag.\n\n\
+                    If --config is \"-\", the resulting configuration is written to stdout instead \
+                    of a file, which makes this subcommand usable as a filter in scripts.",
                 )
                 .arg(
                     Arg::new("skip-check")
@@ -79,9 +248,18 @@ fn handle_commands() -> ArgMatches {
                     Do note that this subcommand will start the program in the modified \
                     environment immediately. This means that it is possible to immediately \
                     invoke runtimes using this method, which may be useful in some cases, \
-                    such as emulating aliases or shims in other version managers.",
+                    such as emulating aliases or shims in other version managers.\n\n\
+                    On Unix, --exec replaces the verune process with the command instead of \
+                    spawning and waiting on a child, so signals and the exit status pass \
+                    straight through to the shell.",
                 )
                 .disable_help_flag(true)
+                .arg(
+                    Arg::new("exec")
+                        .long("exec")
+                        .help("Replace this process with the command instead of spawning a child (Unix only)")
+                        .action(ArgAction::SetTrue),
+                )
                 .arg(
                     arg!([COMMAND]... "The command and its arguments to run")
                         .value_delimiter(' ')
@@ -94,33 +272,50 @@ fn handle_commands() -> ArgMatches {
             Command::new("template")
                 .about("Create template metadata for a runtime")
                 .long_about("This subcommand simply creates the default metadata for a runtime, nothing more.")
-                .arg(arg!(<RUNTIME> "The runtime to create a template for")),
+                .arg(arg!(<RUNTIME> "The runtime to create a template for"))
+                .arg(
+                    Arg::new("ver")
+                        .long("ver")
+                        .help("Write the template in the crate's own compact `ver` format instead of RON")
+                        .action(ArgAction::SetTrue),
+                ),
         )
-        .get_matches()
+        .get_matches_from(args)
 }
 
 macro_rules! verify_config {
     ($x: expr) => {
         if $x.is_none() {
             let bin = env!("CARGO_BIN_NAME");
-            eprintln!(
-                "{}: No configuration data was provided. To create a configuration, \
+            log::error!(
+                "No configuration data was provided. To create a configuration, \
                 please ensure you have a runtime installed and then switch to it using \"{} switch\"",
-                bin, bin
+                bin
             );
-            exit(2);
+            exit(ExitCode::Usage.code());
         }
     };
 }
 
 fn main() {
-    let matches: ArgMatches = handle_commands();
-    let mut error_status: (i32, String, bool) = (
-        1,
-        "No subcommand was passed to verune; for a list of subcommands, please use \"verune help\""
-            .into(),
-        false,
-    );
+    let raw_args: Vec<String> = env::args().collect();
+    let aliases: HashMap<String, String> = conf::load_aliases(prescan_config_path(&raw_args));
+    let expanded_args: Vec<String> = match expand_aliases(raw_args, &aliases) {
+        Ok(args) => args,
+        Err(message) => report_and_exit((ExitCode::Usage, message)),
+    };
+
+    let matches: ArgMatches = handle_commands(expanded_args);
+
+    let level: LevelFilter =
+        resolve_level(matches.get_count("verbose"), matches.get_count("quiet"));
+    let log_format: LogFormat = match matches.get_one::<String>("log-format").map(String::as_str) {
+        Some("line") => LogFormat::Line,
+        _ => LogFormat::Human,
+    };
+    Logger::init(level, log_format);
+
+    let mut exit_code: ExitCode = ExitCode::Usage;
 
     let config_path: PathBuf = if let Some(path) = matches.get_one::<PathBuf>("config") {
         path.to_path_buf()
@@ -129,7 +324,12 @@ fn main() {
     } else {
         ".ver.ron".into()
     };
-    let mut config: Option<HashMap<String, String>> = conf::parse(&config_path).ok();
+    let mut config: Option<HashMap<String, String>> = conf::Builder::new()
+        .source(conf::Source::File(config_path.clone()))
+        .source(conf::Source::Env)
+        .merge()
+        .ok()
+        .map(|(data, _)| data);
     let config_copy: Option<HashMap<String, String>> = if let Some(switch) =
         matches.subcommand_matches("apply")
         && !switch.get_flag("full")
@@ -181,32 +381,83 @@ fn main() {
         }
     }
 
-    if matches.subcommand_matches("check").is_some() {
+    if let Some(data) = matches.get_many::<String>("set") {
+        if config.is_none() {
+            config = Some(HashMap::new());
+        }
+        let config_data: &mut HashMap<String, String> = config.as_mut().unwrap();
+        for entry in data {
+            match entry.split_once('=') {
+                Some((runtime, version)) if !runtime.is_empty() => {
+                    config_data.insert(runtime.to_string(), version.to_string());
+                }
+                _ => {
+                    log::error!(
+                        "Invalid --set value \"{}\"; expected RUNTIME=VERSION",
+                        entry
+                    );
+                    exit(ExitCode::Usage.code());
+                }
+            }
+        }
+    }
+
+    let timeout: Option<Duration> = matches
+        .get_one::<u64>("timeout")
+        .map(|ms| Duration::from_millis(*ms));
+
+    if let Some(matches) = matches.subcommand_matches("check") {
         verify_config!(config);
         let config_data: HashMap<String, String> = config.unwrap();
+        let no_cache: bool = matches.get_flag("no-cache") || conf::is_stdio(&config_path);
+        let config_hash: u64 = cache::hash_config(&config_data);
+        let mut ver_cache: cache::Cache = cache::Cache::load(&config_path);
+        let mut cache_dirty: bool = false;
         let mut should_error: bool = false;
-        error_status = match conf::collect(config_data) {
+        exit_code = match conf::collect(config_data) {
             Ok(data) => {
                 for (runtime, version) in data.iter() {
-                    if let Err(e) = runtime.get_version_search_paths(version) {
-                        should_error = true;
-                        eprintln!("{}: {}: {}", env!("CARGO_BIN_NAME"), runtime.name, e)
+                    let fingerprint: Option<cache::Fingerprint> = if no_cache {
+                        None
+                    } else {
+                        cache::Fingerprint::compute(runtime, version, config_hash).ok()
+                    };
+                    if let Some(fingerprint) = &fingerprint
+                        && ver_cache.is_fresh(&runtime.name, version, fingerprint)
+                    {
+                        continue;
                     }
+                    match runtime.get_version_search_paths_timeout(version, timeout) {
+                        Ok(_) => {
+                            if let Some(fingerprint) = fingerprint {
+                                ver_cache.insert(&runtime.name, version, fingerprint);
+                                cache_dirty = true;
+                            }
+                        }
+                        Err(e) => {
+                            should_error = true;
+                            log::error!("{}: {}", runtime.name, e);
+                        }
+                    }
+                }
+                if cache_dirty && let Err(e) = ver_cache.save(&config_path) {
+                    log::warn!("Could not write check cache: {}", e);
                 }
                 if should_error {
-                    (
-                        1,
-                        format!(
-                            "Issues above must be resolved to safely continue using {}",
-                            env!("CARGO_BIN_NAME")
-                        ),
-                        false,
-                    )
+                    log::error!(
+                        "Issues above must be resolved to safely continue using {}",
+                        env!("CARGO_BIN_NAME")
+                    );
+                    ExitCode::VersionUnavailable
                 } else {
-                    (0, "All runtimes are properly installed".into(), true)
+                    log::info!("All runtimes are properly installed");
+                    ExitCode::Success
                 }
             }
-            Err(e) => (1, format!("Configuration parsing error: {}", e), false),
+            Err(e) => {
+                log::error!("Configuration parsing error: {}", e);
+                ExitCode::ConfigError
+            }
         };
     } else if let Some(matches) = matches.subcommand_matches("apply") {
         let mut config: HashMap<String, String> = if let Some(copy) = config_copy {
@@ -215,7 +466,7 @@ fn main() {
             config.unwrap_or_default()
         };
 
-        error_status = 'errorable: {
+        exit_code = 'errorable: {
             if let Some(rt) = matches.get_one::<String>("RUNTIME").map(|x| x.to_string())
                 && let Some(ver) = matches.get_one::<String>("VERSION").map(|x| x.to_string())
             {
@@ -234,20 +485,14 @@ fn main() {
                     ($runt: expr, $ver: expr, $nesc: tt) => {
                         match Runtime::new($runt) {
                             Ok(rt) => {
-                                if let Err(e) = rt.get_safe_version($ver) {
+                                if let Err(e) = rt.get_safe_version_timeout($ver, timeout) {
                                     conditional_expand!($nesc, { runtime_errors.push('\n') });
-                                    runtime_errors.push_str(
-                                        format!("{}: {}: {}", env!("CARGO_BIN_NAME"), $runt, e)
-                                            .as_str(),
-                                    );
+                                    runtime_errors.push_str(format!("{}: {}", $runt, e).as_str());
                                 }
                             }
                             Err(e) => {
-                                break 'errorable (
-                                    2,
-                                    format!("Could not find runtime \"{}\": {}", $runt, e),
-                                    false,
-                                );
+                                log::error!("Could not find runtime \"{}\": {}", $runt, e);
+                                break 'errorable ExitCode::RuntimeNotFound;
                             }
                         }
                     };
@@ -270,31 +515,34 @@ fn main() {
                         }
                         true
                     });
-                    eprintln!("{}", runtime_errors);
-                    break 'errorable (
-                        1,
+                    for line in runtime_errors.lines() {
+                        log::error!("{}", line);
+                    }
+                    log::error!(
                         "Issues above must be resolved in order to apply configuration changes"
-                            .into(),
-                        false,
                     );
+                    break 'errorable ExitCode::VersionUnavailable;
                 }
             }
 
             if let Ok(data) = ron::to_string(&config) {
-                match write(config_path, data) {
-                    Ok(_) => (0, "Successfully switched configuration".into(), true),
-                    Err(e) => (
-                        1,
-                        format!("Could not write to configuration file: {}", e),
-                        false,
-                    ),
+                match conf::write(&config_path, data) {
+                    Ok(_) => {
+                        let piping_to_stdout: bool =
+                            conf::is_stdio(&config_path) && !std::io::stdout().is_terminal();
+                        if !piping_to_stdout {
+                            log::info!("Successfully switched configuration");
+                        }
+                        ExitCode::Success
+                    }
+                    Err(e) => {
+                        log::error!("Could not write to configuration file: {}", e);
+                        ExitCode::ConfigError
+                    }
                 }
             } else {
-                (
-                    1,
-                    "Could not safely serialize configuration into RON format".into(),
-                    false,
-                )
+                log::error!("Could not safely serialize configuration into RON format");
+                ExitCode::ConfigError
             }
         };
     } else if let Some(matches) = matches.subcommand_matches("scope") {
@@ -305,75 +553,100 @@ fn main() {
                 args.push(i.to_string());
             }
         }
-        error_status = match conf::collect(config.unwrap()) {
-            Ok(config_data) => match exec(args, config_data) {
-                Ok(mut cmd) => {
-                    match cmd
-                        .stdin(Stdio::inherit())
-                        .stdout(Stdio::inherit())
-                        .stderr(Stdio::inherit())
-                        .output()
-                    {
-                        Ok(output) => {
-                            if let Some(code) = output.status.code() {
-                                (
-                                    code,
-                                    "Successfully started program, but error was returned".into(),
-                                    false,
-                                )
-                            } else {
-                                (
-                                    143,
-                                    "Successfully started program, but program was interrupted"
-                                        .into(),
-                                    false,
-                                )
-                            }
+        let use_exec: bool = matches.get_flag("exec");
+        exit_code = 'errorable: {
+            let config_data = match conf::collect(config.unwrap()) {
+                Ok(config_data) => config_data,
+                Err(e) => {
+                    log::error!("Configuration error: {}", e);
+                    break 'errorable ExitCode::ConfigError;
+                }
+            };
+            let mut cmd = match exec(args, config_data) {
+                Ok(cmd) => cmd,
+                Err(e) => {
+                    log::error!("Command error: {}", e);
+                    break 'errorable ExitCode::ExecutionError;
+                }
+            };
+            cmd.stdin(Stdio::inherit())
+                .stdout(Stdio::inherit())
+                .stderr(Stdio::inherit());
+
+            #[cfg(unix)]
+            if use_exec {
+                use std::os::unix::process::CommandExt;
+                log::error!("Failed to exec command: {}", cmd.exec());
+                break 'errorable ExitCode::ExecutionError;
+            }
+            #[cfg(not(unix))]
+            if use_exec {
+                log::warn!(
+                    "--exec is not supported on this platform; falling back to spawn and wait"
+                );
+            }
+
+            match wait_with_timeout(&mut cmd, timeout) {
+                Ok(status) => {
+                    if let Some(code) = status.code() {
+                        if code != 0 {
+                            log::warn!("Successfully started program, but error was returned");
                         }
-                        Err(e) => (1, format!("Execution error: {}", e), false),
+                        ExitCode::Raw(code)
+                    } else {
+                        log::warn!("Successfully started program, but program was interrupted");
+                        ExitCode::Interrupted
                     }
                 }
-                Err(e) => (1, format!("Command error: {}", e), false),
-            },
-            Err(e) => (1, format!("Configuration error: {}", e), false),
+                Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+                    log::error!("Execution error: {}", e);
+                    ExitCode::TimedOut
+                }
+                Err(e) => {
+                    log::error!("Execution error: {}", e);
+                    ExitCode::ExecutionError
+                }
+            }
         };
     } else if let Some(matches) = matches.subcommand_matches("template") {
         let runtime: String = matches.get_one::<String>("RUNTIME").unwrap().to_string();
-        error_status = match Runtime::get_runtime(&runtime) {
+        let use_ver: bool = matches.get_flag("ver");
+        exit_code = match Runtime::get_runtime(&runtime) {
             Ok(mut buf) => {
-                buf.push("meta.ron");
                 let template: RuntimeMetadata = RuntimeMetadata::default();
-                let template_contents: String =
+                let template_contents: String = if use_ver {
+                    buf.push("meta.ver");
+                    template.to_ver_string().unwrap()
+                } else {
+                    buf.push("meta.ron");
                     ron::ser::to_string_pretty(&template, ron::ser::PrettyConfig::default())
-                        .unwrap();
+                        .unwrap()
+                };
                 match write(buf, template_contents) {
-                    Ok(_) => (
-                        0,
-                        format!("Successfully wrote template for runtime \"{}\"", runtime),
-                        true,
-                    ),
-                    Err(e) => (
-                        1,
-                        format!(
+                    Ok(_) => {
+                        log::info!("Successfully wrote template for runtime \"{}\"", runtime);
+                        ExitCode::Success
+                    }
+                    Err(e) => {
+                        log::error!(
                             "Error when writing template for runtime \"{}\": {}",
-                            runtime, e
-                        ),
-                        false,
-                    ),
+                            runtime,
+                            e
+                        );
+                        ExitCode::ConfigError
+                    }
                 }
             }
-            Err(e) => (
-                1,
-                format!("Could not fetch runtime \"{}\": {}", runtime, e),
-                false,
-            ),
+            Err(e) => {
+                log::error!("Could not fetch runtime \"{}\": {}", runtime, e);
+                ExitCode::RuntimeNotFound
+            }
         };
+    } else if matches.subcommand_name().is_none() {
+        log::error!(
+            "No subcommand was passed to verune; for a list of subcommands, please use \"verune help\""
+        );
     }
 
-    if error_status.2 {
-        println!("{}: {}", env!("CARGO_BIN_NAME"), error_status.1);
-    } else if error_status.0 != 0 {
-        eprintln!("{}: {}", env!("CARGO_BIN_NAME"), error_status.1);
-    }
-    exit(error_status.0);
+    exit(exit_code.code());
 }
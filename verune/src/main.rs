@@ -1,12 +1,23 @@
-use clap::{Arg, ArgAction, ArgMatches, Command, arg, command};
-use libver::{Runtime, RuntimeMetadata, conf, exec};
+use clap::{Arg, ArgAction, ArgMatches, Command, arg, command, value_parser};
+use libver::{ExitCode, Runtime, RuntimeMetadata, argv, conf, exec, wait_with_timeout};
+use serde::Serialize;
 use std::{
     collections::HashMap,
     env,
     fs::write,
+    path::PathBuf,
     process::{Stdio, exit},
+    time::Duration,
 };
 
+#[derive(Serialize)]
+struct CheckReportEntry {
+    runtime: String,
+    version: String,
+    status: &'static str,
+    error: Option<String>,
+}
+
 fn handle_commands() -> ArgMatches {
     command!()
         .arg(arg!(-g --global "Use the global configuration").action(ArgAction::SetTrue))
@@ -15,6 +26,34 @@ fn handle_commands() -> ArgMatches {
                 .action(ArgAction::Set)
                 .value_name("CONFIG"),
         )
+        .arg(
+            Arg::new("set")
+                .short('s')
+                .long("set")
+                .help("Override a runtime's version for this invocation, without touching the configuration file")
+                .action(ArgAction::Append)
+                .global(true)
+                .number_of_values(1)
+                .value_name("RUNTIME=VERSION"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .help("The output format to use")
+                .action(ArgAction::Set)
+                .global(true)
+                .value_parser(["human", "json"])
+                .default_value("human"),
+        )
+        .arg(
+            Arg::new("timeout")
+                .long("timeout")
+                .help("The maximum time, in milliseconds, to wait on any external process")
+                .action(ArgAction::Set)
+                .global(true)
+                .value_parser(value_parser!(u64))
+                .value_name("MILLISECONDS"),
+        )
         .subcommand(
             Command::new("check")
                 .about("Checks all runtime versions for their existence")
@@ -58,9 +97,24 @@ fn handle_commands() -> ArgMatches {
                     Do note that this subcommand will start the program in the modified \
                     environment immediately. This means that it is possible to immediately \
                     invoke runtimes using this method, which may be useful in some cases, \
-                    such as emulating aliases or shims in other version managers.",
+                    such as emulating aliases or shims in other version managers.\n\n\
+                    On Unix, --exec replaces the verune process with the command instead of \
+                    spawning and waiting on a child, so signals and the exit status pass \
+                    straight through to the shell.",
                 )
                 .disable_help_flag(true)
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .help("Print the resolved command and runtime selection instead of running it")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("exec")
+                        .long("exec")
+                        .help("Replace this process with the command instead of spawning a child (Unix only)")
+                        .action(ArgAction::SetTrue),
+                )
                 .arg(
                     arg!([COMMAND]... "The command and its arguments to run")
                         .value_delimiter(' ')
@@ -73,11 +127,156 @@ fn handle_commands() -> ArgMatches {
             Command::new("template")
                 .about("Create template metadata for a runtime")
                 .long_about("This subcommand simply creates the default metadata for a runtime, nothing more.")
-                .arg(arg!(<RUNTIME> "The runtime to create a template for")),
+                .arg(arg!(<RUNTIME> "The runtime to create a template for"))
+                .arg(
+                    Arg::new("ver")
+                        .long("ver")
+                        .help("Write the template in the crate's own compact `ver` format instead of RON")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("init")
+                .about("Generate shell integration for automatic, directory-aware scoping")
+                .long_about(
+                    "This subcommand prints shell code that should be evaluated by the \
+                    shell's startup file (e.g. with `eval \"$(verune init bash)\"`). The \
+                    generated code installs a hook that runs before every prompt, walks up \
+                    from the current directory looking for the nearest \".ver.ron\", and if \
+                    one is found, applies its scope to the current shell by calling back into \
+                    \"verune\" for the same $PATH assembly that \"scope\" uses, without \
+                    spawning a child process. The resolved directory is cached so the hook \
+                    only recomputes when the configuration path or its modification time \
+                    changes.",
+                )
+                .arg(arg!(<SHELL> "The shell to generate activation code for").value_parser(["bash", "zsh", "fish"])),
+        )
+        .subcommand(
+            Command::new("__resolve-config")
+                .hide(true)
+                .about("Prints the nearest \".ver.ron\" above the working directory, for shell integration"),
+        )
+        .subcommand(
+            Command::new("__resolve-path")
+                .hide(true)
+                .about("Prints the $PATH that \"scope\" would use, for shell integration"),
         )
         .get_matches()
 }
 
+/// Walks up from `start` looking for the nearest `.ver.ron`, returning its path if found.
+fn find_nearest_config(start: &std::path::Path) -> Option<PathBuf> {
+    let mut dir: &std::path::Path = start;
+    loop {
+        let candidate: PathBuf = dir.join(".ver.ron");
+        if candidate.try_exists().unwrap_or(false) {
+            return Some(candidate);
+        }
+        dir = dir.parent()?;
+    }
+}
+
+/// Generates the shell activation code for `verune init <shell>`.
+fn generate_init_script(shell: &str, bin: &str) -> String {
+    match shell {
+        "bash" => format!(
+            r#"__verune_hook() {{
+    local config
+    config="$({bin} __resolve-config 2>/dev/null)"
+    if [ -z "$config" ]; then
+        if [ -n "$__VERUNE_CACHED_CONFIG" ]; then
+            export PATH="$VER_BASE_PATH"
+            unset VER_OVERRIDE
+            __VERUNE_CACHED_CONFIG=""
+            __VERUNE_CACHED_MTIME=""
+        fi
+        return
+    fi
+    local mtime
+    mtime="$(command stat -c %Y "$config" 2>/dev/null || command stat -f %m "$config" 2>/dev/null)"
+    if [ "$config" != "$__VERUNE_CACHED_CONFIG" ] || [ "$mtime" != "$__VERUNE_CACHED_MTIME" ]; then
+        local resolved
+        resolved="$({bin} --config "$config" __resolve-path)"
+        if [ -n "$resolved" ]; then
+            export PATH="$resolved:$VER_BASE_PATH"
+            export VER_OVERRIDE=1
+        fi
+        __VERUNE_CACHED_CONFIG="$config"
+        __VERUNE_CACHED_MTIME="$mtime"
+    fi
+}}
+export VER_BASE_PATH="$PATH"
+case ";$PROMPT_COMMAND;" in
+    *";__verune_hook;"*) ;;
+    *) PROMPT_COMMAND="__verune_hook${{PROMPT_COMMAND:+;$PROMPT_COMMAND}}" ;;
+esac
+"#,
+            bin = bin
+        ),
+        "zsh" => format!(
+            r#"__verune_hook() {{
+    local config
+    config="$({bin} __resolve-config 2>/dev/null)"
+    if [ -z "$config" ]; then
+        if [ -n "$__VERUNE_CACHED_CONFIG" ]; then
+            export PATH="$VER_BASE_PATH"
+            unset VER_OVERRIDE
+            __VERUNE_CACHED_CONFIG=""
+            __VERUNE_CACHED_MTIME=""
+        fi
+        return
+    fi
+    local mtime
+    mtime="$(command stat -c %Y "$config" 2>/dev/null || command stat -f %m "$config" 2>/dev/null)"
+    if [[ "$config" != "$__VERUNE_CACHED_CONFIG" || "$mtime" != "$__VERUNE_CACHED_MTIME" ]]; then
+        local resolved
+        resolved="$({bin} --config "$config" __resolve-path)"
+        if [ -n "$resolved" ]; then
+            export PATH="$resolved:$VER_BASE_PATH"
+            export VER_OVERRIDE=1
+        fi
+        __VERUNE_CACHED_CONFIG="$config"
+        __VERUNE_CACHED_MTIME="$mtime"
+    fi
+}}
+export VER_BASE_PATH="$PATH"
+autoload -Uz add-zsh-hook
+add-zsh-hook precmd __verune_hook
+add-zsh-hook chpwd __verune_hook
+"#,
+            bin = bin
+        ),
+        "fish" => format!(
+            r#"function __verune_hook --on-variable PWD
+    set -l config ({bin} __resolve-config 2>/dev/null)
+    if test -z "$config"
+        if set -q __verune_cached_config
+            set -gx PATH $VER_BASE_PATH
+            set -e VER_OVERRIDE
+            set -e __verune_cached_config
+            set -e __verune_cached_mtime
+        end
+        return
+    end
+    set -l mtime (stat -c %Y "$config" 2>/dev/null; or stat -f %m "$config" 2>/dev/null)
+    if not set -q __verune_cached_config; or test "$config" != "$__verune_cached_config"; or test "$mtime" != "$__verune_cached_mtime"
+        set -l resolved ({bin} --config "$config" __resolve-path)
+        if test -n "$resolved"
+            set -gx PATH $resolved $VER_BASE_PATH
+            set -gx VER_OVERRIDE 1
+        end
+        set -g __verune_cached_config "$config"
+        set -g __verune_cached_mtime "$mtime"
+    end
+end
+set -gx VER_BASE_PATH $PATH
+"#,
+            bin = bin
+        ),
+        _ => unreachable!("clap restricts SHELL to bash, zsh, or fish"),
+    }
+}
+
 macro_rules! verify_config {
     ($x: expr) => {
         if $x.is_none() {
@@ -87,48 +286,135 @@ macro_rules! verify_config {
                 please ensure you have a runtime installed and then switch to it using \"{} switch\"",
                 bin, bin
             );
-            exit(2);
+            exit(ExitCode::Usage.code());
         }
     };
 }
 
 fn main() {
     let matches: ArgMatches = handle_commands();
-    let mut error_status: (i32, String, bool) = (
-        1,
+    let mut error_status: (ExitCode, String) = (
+        ExitCode::Usage,
         "No subcommand was passed to verune; for a list of subcommands, please use \"verstring help\"".into(),
-        false,
     );
 
-    let config_path: String = if let Some(path) = matches.get_one::<String>("config") {
-        path.to_string()
+    let explicit_config: bool =
+        matches.get_one::<String>("config").is_some() || matches.get_flag("global");
+    let config_path: PathBuf = if let Some(path) = matches.get_one::<String>("config") {
+        PathBuf::from(path)
+    } else if matches.get_flag("global") {
+        conf::global_path().unwrap_or_else(|_| PathBuf::from(".ver.ron"))
     } else {
-        ".ver.ron".into()
+        env::current_dir()
+            .ok()
+            .and_then(conf::find_local)
+            .unwrap_or_else(|| PathBuf::from(".ver.ron"))
     };
-    let mut config: Option<HashMap<String, String>> = libver::conf::parse(&config_path).ok();
+    let mut config: Option<HashMap<String, String>> = {
+        let mut builder: conf::Builder = conf::Builder::new();
+        if explicit_config {
+            builder = builder.source(conf::Source::File(config_path.clone()));
+        } else {
+            if let Ok(global) = conf::global_path()
+                && global.try_exists().unwrap_or(false)
+            {
+                builder = builder.source(conf::Source::File(global));
+            }
+            if let Some(local) = env::current_dir().ok().and_then(conf::find_local) {
+                builder = builder.source(conf::Source::File(local));
+            }
+        }
+        builder
+            .source(conf::Source::Env)
+            .merge()
+            .ok()
+            .map(|(data, _)| data)
+    };
+    let timeout: Option<Duration> = matches
+        .get_one::<u64>("timeout")
+        .map(|ms| Duration::from_millis(*ms));
+
+    if let Some(data) = matches.get_many::<String>("set") {
+        if config.is_none() {
+            config = Some(HashMap::new());
+        }
+        let config_data: &mut HashMap<String, String> = config.as_mut().unwrap();
+        for entry in data {
+            match entry.split_once('=') {
+                Some((runtime, version)) if !runtime.is_empty() => {
+                    config_data.insert(runtime.to_string(), version.to_string());
+                }
+                _ => {
+                    eprintln!(
+                        "{}: Invalid --set value \"{}\"; expected RUNTIME=VERSION",
+                        env!("CARGO_BIN_NAME"),
+                        entry
+                    );
+                    exit(ExitCode::Usage.code());
+                }
+            }
+        }
+    }
 
     if matches.subcommand_matches("check").is_some() {
         verify_config!(config);
+        let json_format: bool =
+            matches.get_one::<String>("format").map(String::as_str) == Some("json");
         let config_data: HashMap<String, String> = config.unwrap();
         let data: HashMap<Runtime, String> = libver::conf::unsafe_collect(config_data);
         let mut should_error: bool = false;
+        let mut report: Vec<CheckReportEntry> = Vec::with_capacity(data.len());
         for (runtime, version) in data.iter() {
-            if let Err(e) = runtime.get_safe_version(version.to_string()) {
-                should_error = true;
-                eprintln!("{}: {}: {}", env!("CARGO_BIN_NAME"), runtime.name, e)
+            match runtime.get_safe_version_timeout(version.to_string(), timeout) {
+                Ok(_) => report.push(CheckReportEntry {
+                    runtime: runtime.name.clone(),
+                    version: version.clone(),
+                    status: "ok",
+                    error: None,
+                }),
+                Err(e) => {
+                    should_error = true;
+                    if !json_format {
+                        eprintln!("{}: {}: {}", env!("CARGO_BIN_NAME"), runtime.name, e);
+                    }
+                    report.push(CheckReportEntry {
+                        runtime: runtime.name.clone(),
+                        version: version.clone(),
+                        status: "missing",
+                        error: Some(e.to_string()),
+                    });
+                }
+            }
+        }
+        if json_format {
+            match serde_json::to_string(&report) {
+                Ok(data) => println!("{}", data),
+                Err(e) => eprintln!(
+                    "{}: Could not serialize check report: {}",
+                    env!("CARGO_BIN_NAME"),
+                    e
+                ),
             }
         }
         error_status = if should_error {
             (
-                1,
-                format!(
-                    "Issues above must be resolved to safely continue using {}",
-                    env!("CARGO_BIN_NAME")
-                ),
-                false,
+                ExitCode::VersionUnavailable,
+                if json_format {
+                    String::new()
+                } else {
+                    format!(
+                        "Issues above must be resolved to safely continue using {}",
+                        env!("CARGO_BIN_NAME")
+                    )
+                },
             )
+        } else if json_format {
+            (ExitCode::Success, String::new())
         } else {
-            (0, "All runtimes are properly installed".into(), true)
+            (
+                ExitCode::Success,
+                "All runtimes are properly installed".into(),
+            )
         };
     } else if let Some(matches) = matches.subcommand_matches("switch") {
         if config.is_none() {
@@ -136,15 +422,16 @@ fn main() {
         }
         let runtime: String = matches.get_one::<String>("RUNTIME").unwrap().to_string();
         let version: String = matches.get_one::<String>("VERSION").unwrap().to_string();
-        let mut potential_error: Option<(i32, String, bool)> = None;
+        let mut potential_error: Option<(ExitCode, String)> = None;
         error_status = if matches.get_flag("skip-check") || {
             match Runtime::new(runtime.clone()) {
-                Ok(data) => data.get_safe_version(version.to_string()).is_ok(),
+                Ok(data) => data
+                    .get_safe_version_timeout(version.to_string(), timeout)
+                    .is_ok(),
                 Err(e) => {
                     potential_error = Some((
-                        1,
+                        ExitCode::RuntimeNotFound,
                         format!("Could not find runtime \"{}\": {}", runtime, e),
-                        false,
                     ));
                     false
                 }
@@ -152,39 +439,38 @@ fn main() {
         } {
             let mut config_data: HashMap<String, String> = config.unwrap();
             config_data.insert(runtime.clone(), version.to_string());
+            if let Some(parent) = config_path.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
             if let Ok(data) = ron::to_string(&config_data) {
                 match write(config_path, data) {
                     Ok(_) => (
-                        0,
+                        ExitCode::Success,
                         format!(
                             "Successfully switched runtime \"{}\" to version {}",
                             runtime, version
                         ),
-                        true,
                     ),
                     Err(e) => (
-                        1,
+                        ExitCode::ConfigError,
                         format!("Could not write to configuration file: {}", e),
-                        false,
                     ),
                 }
             } else {
                 (
-                    1,
+                    ExitCode::ConfigError,
                     "Could not safely serialize configuration file".to_string(),
-                    false,
                 )
             }
         } else if let Some(error) = potential_error {
             error
         } else {
             (
-                2,
+                ExitCode::VersionUnavailable,
                 format!(
                     "Runtime \"{}\" version {} could not be found",
                     runtime, version
                 ),
-                false,
             )
         };
     } else if let Some(matches) = matches.subcommand_matches("scope") {
@@ -195,75 +481,152 @@ fn main() {
                 args.push(i.to_string());
             }
         }
+        let dry_run: bool = matches.get_flag("dry-run");
+        let use_exec: bool = matches.get_flag("exec");
         error_status = match conf::collect(config.unwrap()) {
-            Ok(config_data) => match exec(args.into(), config_data) {
-                Ok(mut cmd) => {
-                    match cmd
-                        .stdin(Stdio::inherit())
+            Ok(config_data) => 'scope: {
+                let selection: Vec<String> = argv::to_args(&config_data);
+                if dry_run {
+                    match exec(args, config_data) {
+                        Ok(cmd) => {
+                            let mut line: Vec<String> =
+                                vec![cmd.get_program().to_string_lossy().into_owned()];
+                            line.extend(
+                                cmd.get_args().map(|arg| arg.to_string_lossy().into_owned()),
+                            );
+                            line.extend(selection);
+                            println!("{}", line.join(" "));
+                            (ExitCode::Success, String::new())
+                        }
+                        Err(e) => (ExitCode::ExecutionError, format!("Command error: {}", e)),
+                    }
+                } else {
+                    let mut cmd = match exec(args, config_data) {
+                        Ok(cmd) => cmd,
+                        Err(e) => {
+                            break 'scope (
+                                ExitCode::ExecutionError,
+                                format!("Command error: {}", e),
+                            );
+                        }
+                    };
+                    cmd.stdin(Stdio::inherit())
                         .stdout(Stdio::inherit())
-                        .stderr(Stdio::inherit())
-                        .output()
-                    {
-                        Ok(output) => {
-                            if let Some(code) = output.status.code() {
+                        .stderr(Stdio::inherit());
+
+                    #[cfg(unix)]
+                    if use_exec {
+                        use std::os::unix::process::CommandExt;
+                        break 'scope (
+                            ExitCode::ExecutionError,
+                            format!("Failed to exec command: {}", cmd.exec()),
+                        );
+                    }
+                    #[cfg(not(unix))]
+                    if use_exec {
+                        eprintln!(
+                            "{}: --exec is not supported on this platform; falling back to spawn and wait",
+                            env!("CARGO_BIN_NAME")
+                        );
+                    }
+
+                    match wait_with_timeout(&mut cmd, timeout) {
+                        Ok(status) => {
+                            if let Some(code) = status.code() {
                                 (
-                                    code,
+                                    ExitCode::Raw(code),
                                     "Successfully started program, but error was returned".into(),
-                                    false,
                                 )
                             } else {
                                 (
-                                    143,
+                                    ExitCode::Interrupted,
                                     "Successfully started program, but program was interrupted"
                                         .into(),
-                                    false,
                                 )
                             }
                         }
-                        Err(e) => (1, format!("Execution error: {}", e), false),
+                        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+                            (ExitCode::TimedOut, format!("Execution error: {}", e))
+                        }
+                        Err(e) => (ExitCode::ExecutionError, format!("Execution error: {}", e)),
                     }
                 }
-                Err(e) => (1, format!("Command error: {}", e), false),
-            },
-            Err(e) => (1, format!("Configuration error: {}", e), false),
+            }
+            Err(e) => (ExitCode::ConfigError, format!("Configuration error: {}", e)),
         };
     } else if let Some(matches) = matches.subcommand_matches("template") {
         let runtime: String = matches.get_one::<String>("RUNTIME").unwrap().to_string();
+        let use_ver: bool = matches.get_flag("ver");
         error_status = match Runtime::get_runtime(runtime.as_str()) {
             Ok(mut buf) => {
-                buf.push("meta.ron");
                 let template: RuntimeMetadata = RuntimeMetadata::default();
-                let template_contents: String =
+                let template_contents: String = if use_ver {
+                    buf.push("meta.ver");
+                    template.to_ver_string().unwrap()
+                } else {
+                    buf.push("meta.ron");
                     ron::ser::to_string_pretty(&template, ron::ser::PrettyConfig::default())
-                        .unwrap();
+                        .unwrap()
+                };
                 match write(buf, template_contents) {
                     Ok(_) => (
-                        0,
+                        ExitCode::Success,
                         format!("Successfully wrote template for runtime \"{}\"", runtime),
-                        true,
                     ),
                     Err(e) => (
-                        1,
+                        ExitCode::ConfigError,
                         format!(
                             "Error when writing template for runtime \"{}\": {}",
                             runtime, e
                         ),
-                        false,
                     ),
                 }
             }
             Err(e) => (
-                1,
+                ExitCode::RuntimeNotFound,
                 format!("Could not fetch runtime \"{}\": {}", runtime, e),
-                false,
             ),
         };
+    } else if let Some(matches) = matches.subcommand_matches("init") {
+        let shell: &str = matches.get_one::<String>("SHELL").unwrap().as_str();
+        print!("{}", generate_init_script(shell, env!("CARGO_BIN_NAME")));
+        error_status = (ExitCode::Success, String::new());
+    } else if matches.subcommand_matches("__resolve-config").is_some() {
+        if let Ok(cwd) = env::current_dir()
+            && let Some(found) = find_nearest_config(&cwd)
+        {
+            println!("{}", found.display());
+        }
+        error_status = (ExitCode::Success, String::new());
+    } else if matches.subcommand_matches("__resolve-path").is_some() {
+        error_status = if let Some(config_data) = config.clone() {
+            match conf::collect(config_data) {
+                Ok(data) => match exec(Vec::<String>::new(), data) {
+                    Ok(cmd) => {
+                        if let Some(Some(path)) = cmd
+                            .get_envs()
+                            .find(|(key, _)| key.to_str() == Some("PATH"))
+                            .map(|(_, v)| v)
+                        {
+                            println!("{}", path.to_string_lossy());
+                        }
+                        (ExitCode::Success, String::new())
+                    }
+                    Err(e) => (ExitCode::ExecutionError, format!("Command error: {}", e)),
+                },
+                Err(e) => (ExitCode::ConfigError, format!("Configuration error: {}", e)),
+            }
+        } else {
+            (ExitCode::Success, String::new())
+        };
     }
 
-    if error_status.2 {
-        println!("{}: {}", env!("CARGO_BIN_NAME"), error_status.1);
-    } else if error_status.0 != 0 {
-        eprintln!("{}: {}", env!("CARGO_BIN_NAME"), error_status.1);
+    if !error_status.1.is_empty() {
+        if error_status.0 == ExitCode::Success {
+            println!("{}: {}", env!("CARGO_BIN_NAME"), error_status.1);
+        } else if error_status.0.code() != 0 {
+            eprintln!("{}: {}", env!("CARGO_BIN_NAME"), error_status.1);
+        }
     }
-    exit(error_status.0);
+    exit(error_status.0.code());
 }